@@ -1,68 +1,164 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    entry_point, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdResult, SubMsg, WasmMsg,
 };
 
 use cw2::{get_contract_version, set_contract_version};
+use cw_utils::parse_reply_instantiate_data;
+use semver::Version;
+
+use crate::token::InstantiateMsg as Cw721InstantiateMsg;
 
 use crate::error::ContractError;
-use crate::execute::{execute_enable_public_mint, execute_enable_reveal, execute_init, execute_mint, execute_reveal, execute_update_config, execute_private_whitelist_approve, execute_private_whitelist_remove, execute_withdraw_funds, execute_public_whitelist_remove, execute_public_whitelist_approve, execute_enable_normal_whitelist};
+use crate::execute::{execute_enable_public_mint, execute_enable_reveal, execute_init, execute_mint, execute_mint_reserved, execute_receive, execute_reveal, execute_update_config, execute_private_whitelist_approve, execute_private_whitelist_remove, execute_withdraw_funds, execute_public_whitelist_remove, execute_public_whitelist_approve, execute_enable_normal_whitelist, execute_private_whitelist_merkle_root, execute_public_whitelist_merkle_root, validate_address, validate_cw20_price, validate_escrow, validate_fee_asset, validate_merkle_root, validate_payees, validate_platform_fee, validate_whitelist_tiers, execute_distribute, execute_update_platform_address, execute_whitelist_add, execute_whitelist_applications_approve, execute_whitelist_applications_reject, execute_claim_refund, execute_transfer_ownership, execute_accept_ownership, execute_renounce_ownership, execute_transfer_artist, execute_accept_artist, execute_pause, execute_unpause, update_config};
+use crate::migrate::migrate_from_v1;
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::query::{query_config, query_token_statuses, query_whitelist_member};
-use crate::state::{State, WhitelistMember, STATE, PUBLIC_WHITELIST, PRIVATE_WHITELIST};
-
-// Mainnet
-pub static DENOM: &str = "aarch";
-// Testnet
-// pub static DENOM: &str = "aconst";
+use crate::query::{query_config, query_mint_eligibility, query_minted_count, query_ownership, query_payees, query_sale_stats, query_token_statuses, query_whitelist_member, query_whitelist_applications, query_whitelist_members, query_whitelist_stats};
+use crate::state::{ReservedMinted, State, WhitelistKind, WhitelistMember, STATE, MINTED_COUNT, RESERVED_MINTED};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "whitelist-minter";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+pub const BURN_REFUND_REPLY_ID: u64 = 2;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    // Create Config State
+    // Reserved allocations must fit in the supply
+    match msg.total_reserved.checked_add(msg.total_reserved_founders) {
+        Some(reserved) if reserved <= msg.supply => {}
+        _ => return Err(ContractError::SourceMetadata {}),
+    }
+
+    let schedule = msg.schedule.unwrap_or_default();
+    if !schedule.is_valid() {
+        return Err(ContractError::InvalidSchedule {});
+    }
+
+    validate_merkle_root(&msg.private_whitelist_merkle_root)?;
+    validate_merkle_root(&msg.public_whitelist_merkle_root)?;
+    validate_whitelist_tiers(&msg.whitelist_tiers)?;
+
+    let artist = validate_address(deps.api, &msg.reserved_recipient)?;
+    let cw20_price = msg
+        .cw20_price
+        .map(|price| validate_cw20_price(deps.api, price))
+        .transpose()?;
+    let payees = validate_payees(deps.api, msg.payees)?;
+    let platform_fee = msg
+        .platform_fee
+        .map(|platform_fee| validate_platform_fee(deps.api, platform_fee))
+        .transpose()?;
+
+    // Create Config State. The cw721 address is set on reply,
+    // once the token contract is instantiated
     let state = State {
         owner: info.sender.clone(),
-        cw721: msg.cw721.clone(),
-        artist: msg.reserved_recipient.clone(),
+        cw721: Addr::unchecked(""),
+        artist,
         supply: msg.supply,
         phase: Default::default(),
+        paused: false,
+        schedule,
+        total_reserved: msg.total_reserved,
+        total_reserved_founders: msg.total_reserved_founders,
+        shuffle_token_ids: msg.shuffle_token_ids,
         private_whitelist_allowance: msg.private_whitelist_allowance,
         public_whitelist_allowance: msg.public_whitelist_allowance,
+        private_whitelist_price: msg.private_whitelist_price.unwrap_or(msg.price),
+        public_whitelist_price: msg.public_whitelist_price.unwrap_or(msg.price),
+        whitelist_tiers: msg.whitelist_tiers,
+        whitelist_deposit: msg.whitelist_deposit.unwrap_or_default(),
         price: msg.price,
+        denom: msg.denom,
+        cw20_price,
         name_prefix: msg.naming_prefix,
+        payees,
+        platform_fee,
+        private_whitelist_merkle_root: msg.private_whitelist_merkle_root,
+        public_whitelist_merkle_root: msg.public_whitelist_merkle_root,
+        voucher_signer: msg.voucher_signer,
+        escrow: msg.escrow,
     };
+    if !state.payees_valid() {
+        return Err(ContractError::InvalidPayees {});
+    }
+    validate_fee_asset(&state.platform_fee, &state.cw20_price)?;
+    validate_escrow(&state, &None, &env)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    RESERVED_MINTED.save(deps.storage, &ReservedMinted::default())?;
+    MINTED_COUNT.save(deps.storage, &0)?;
 
     // Create Whitelist
     for member in msg.public_whitelist_members.iter() {
-        let whitelist_member = WhitelistMember { whitelisted: true };
-        PUBLIC_WHITELIST.update(deps.storage, member, |existing| match existing {
-            None => Ok(whitelist_member.clone()),
-            Some(_) => Err(ContractError::InvalidInput {}),
-        })?;
+        let member = validate_address(deps.api, member)?;
+        if WhitelistKind::Public.members().has(deps.storage, &member) {
+            return Err(ContractError::DuplicateMember { address: member.to_string() });
+        }
+        let whitelist_member = WhitelistMember { whitelisted: true, ..Default::default() };
+        WhitelistKind::Public.save_member(deps.storage, &state, &member, &whitelist_member)?;
     }
     for member in msg.private_whitelist_members.iter() {
-        let whitelist_member = WhitelistMember { whitelisted: true };
-        PRIVATE_WHITELIST.update(deps.storage, member, |existing| match existing {
-            None => Ok(whitelist_member.clone()),
-            Some(_) => Err(ContractError::InvalidInput {}),
-        })?;
+        let member = validate_address(deps.api, member)?;
+        if WhitelistKind::Private.members().has(deps.storage, &member) {
+            return Err(ContractError::DuplicateMember { address: member.to_string() });
+        }
+        let whitelist_member = WhitelistMember { whitelisted: true, ..Default::default() };
+        WhitelistKind::Private.save_member(deps.storage, &state, &member, &whitelist_member)?;
     }
 
+    let token_msg = Cw721InstantiateMsg {
+        name: msg.token_name.clone(),
+        symbol: msg.token_symbol,
+        minter: env.contract.address.to_string(),
+    };
+    let instantiate_token = WasmMsg::Instantiate {
+        admin: Some(info.sender.to_string()),
+        code_id: msg.token_code_id,
+        msg: to_json_binary(&token_msg)?,
+        funds: vec![],
+        label: msg.token_name,
+    };
+
     Ok(Response::new()
         .add_attribute("action", "instantiate")
         .add_attribute("owner", info.sender)
-        .add_attribute("cw721", msg.cw721)
-        .add_attribute("supply", msg.supply.to_string()))
+        .add_attribute("supply", msg.supply.to_string())
+        .add_submessage(SubMsg::reply_on_success(instantiate_token, INSTANTIATE_TOKEN_REPLY_ID)))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_TOKEN_REPLY_ID => reply_instantiate_token(deps, msg),
+        // Only replied on error, as the holder hadn't approved
+        // the burn
+        BURN_REFUND_REPLY_ID => Err(ContractError::BurnNotApproved {}),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+fn reply_instantiate_token(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    // Store the address of the token contract we instantiated
+    let res = parse_reply_instantiate_data(msg)?;
+    let cw721 = validate_address(deps.api, &res.contract_address)?;
+    STATE.update(deps.storage, |mut state| -> StdResult<_> {
+        state.cw721 = cw721.clone();
+        Ok(state)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate_token")
+        .add_attribute("cw721", cw721))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -75,44 +171,91 @@ pub fn execute(
     match msg {
         ExecuteMsg::Mint(msg) => execute_mint(deps, env, info, msg),
         ExecuteMsg::Reveal(msg) => execute_reveal(deps, env, info, msg),
+        ExecuteMsg::WhitelistAdd(msg) => execute_whitelist_add(deps, env, info, msg),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::Distribute(msg) => execute_distribute(deps, env, info, msg),
+        ExecuteMsg::ClaimRefund(msg) => execute_claim_refund(deps, env, info, msg),
 
         // Artist only
         ExecuteMsg::Withdraw(msg) => execute_withdraw_funds(deps, env, info, msg),
+        ExecuteMsg::TransferArtist(msg) => execute_transfer_artist(deps, env, info, msg),
+
+        // Pending owner or artist only
+        ExecuteMsg::AcceptOwnership(msg) => execute_accept_ownership(deps, env, info, msg),
+        ExecuteMsg::AcceptArtist(msg) => execute_accept_artist(deps, env, info, msg),
 
         // Admin only
         ExecuteMsg::Initialize(msg) => execute_init(deps, env, info, msg),
+        ExecuteMsg::MintReserved(msg) => execute_mint_reserved(deps, env, info, msg),
         ExecuteMsg::EnableNormalWhitelist(msg) => execute_enable_normal_whitelist(deps, env, info, msg),
         ExecuteMsg::EnablePublicMint(msg) => execute_enable_public_mint(deps, env, info, msg),
         ExecuteMsg::EnableReveal(msg) => execute_enable_reveal(deps, env, info, msg),
         ExecuteMsg::PublicWhitelistRemove(msg) => execute_public_whitelist_remove(deps, env, info, msg),
+        ExecuteMsg::PrivateWhitelistMerkleRoot(msg) => execute_private_whitelist_merkle_root(deps, env, info, msg),
+        ExecuteMsg::PublicWhitelistMerkleRoot(msg) => execute_public_whitelist_merkle_root(deps, env, info, msg),
         ExecuteMsg::PublicWhitelistApprove(msg) => execute_public_whitelist_approve(deps, env, info, msg),
         ExecuteMsg::PrivateWhitelistRemove(msg) => execute_private_whitelist_remove(deps, env, info, msg),
         ExecuteMsg::PrivateWhitelistApprove(msg) => execute_private_whitelist_approve(deps, env, info, msg),
-        ExecuteMsg::UpdateConfig { config } => execute_update_config(deps, env, info, config),
+        ExecuteMsg::WhitelistApplicationsApprove(msg) => execute_whitelist_applications_approve(deps, env, info, msg),
+        ExecuteMsg::WhitelistApplicationsReject(msg) => execute_whitelist_applications_reject(deps, env, info, msg),
+        ExecuteMsg::UpdatePlatformAddress(msg) => execute_update_platform_address(deps, env, info, msg),
+        ExecuteMsg::Pause {} => execute_pause(deps, env, info),
+        ExecuteMsg::Unpause {} => execute_unpause(deps, env, info),
+        ExecuteMsg::TransferOwnership(msg) => execute_transfer_ownership(deps, env, info, msg),
+        ExecuteMsg::RenounceOwnership(msg) => execute_renounce_ownership(deps, env, info, msg),
+        ExecuteMsg::UpdateConfig(msg) => execute_update_config(deps, env, info, msg),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     Ok(match msg {
-        QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::Config {} => to_json_binary(&query_config(deps, env)?),
         QueryMsg::TokenStatuses { token_ids } => to_json_binary(&query_token_statuses(deps, token_ids)?),
         QueryMsg::Whitelist { address } => to_json_binary(&query_whitelist_member(deps, address)?),
+        QueryMsg::MintedCount {} => to_json_binary(&query_minted_count(deps)?),
+        QueryMsg::MintEligibility { address } => to_json_binary(&query_mint_eligibility(deps, env, address)?),
+        QueryMsg::WhitelistMembers { list, start_after, limit } => to_json_binary(&query_whitelist_members(deps, list, start_after, limit)?),
+        QueryMsg::WhitelistStats { list } => to_json_binary(&query_whitelist_stats(deps, list)?),
+        QueryMsg::WhitelistApplications { start_after, limit } => to_json_binary(&query_whitelist_applications(deps, start_after, limit)?),
+        QueryMsg::Payees { cw20 } => to_json_binary(&query_payees(deps, env, cw20)?),
+        QueryMsg::SaleStats {} => to_json_binary(&query_sale_stats(deps)?),
+        QueryMsg::Ownership {} => to_json_binary(&query_ownership(deps)?),
     }?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    let original_version = get_contract_version(deps.storage)?;
-    let name = CONTRACT_NAME.to_string();
-    let version = CONTRACT_VERSION.to_string();
-    if original_version.contract != name {
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
         return Err(ContractError::InvalidInput {});
     }
-    if original_version.version >= version {
-        return Err(ContractError::InvalidInput {});
+
+    // Only upgrades are allowed
+    let stored_version: Version = stored.version.parse()?;
+    let version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version >= version {
+        return Err(ContractError::CannotMigrate {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    // Migration steps, oldest first
+    if stored_version < Version::new(1, 1, 0) {
+        migrate_from_v1(deps.branch(), msg.denom, msg.minted_count)?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+    if let Some(config) = msg.config {
+        let state = STATE.load(deps.storage)?;
+        let update = update_config(deps.branch(), &env, state, config)?;
+        res = res.add_attributes(update.attributes);
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::default())
+    Ok(res)
 }
@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError, Uint128, VerificationError};
+use cw_utils::ParseReplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,15 +7,81 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    Verification(#[from] VerificationError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Cannot migrate from version {stored} to {current}")]
+    CannotMigrate { stored: String, current: String },
+
+    #[error("Migrating from v1 requires the minted count")]
+    MissingMintedCount {},
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
     #[error("Unauthorized")]
     Unauthorized {},
 
     #[error("Invalid input")]
     InvalidInput {},
 
+    #[error("Invalid address {address}")]
+    InvalidAddress { address: String },
+
     #[error("Contract already initialized")]
     Initialized {},
 
+    #[error("Phase schedule must start phases in order")]
+    InvalidSchedule {},
+
+    #[error("Merkle root must be a hex encoded sha256 hash")]
+    InvalidMerkleRoot {},
+
+    #[error("Merkle proof must contain hex encoded sha256 hashes")]
+    InvalidMerkleProof {},
+
+    #[error("Mint voucher is not valid for this mint")]
+    InvalidVoucher {},
+
+    #[error("Mint voucher expired")]
+    VoucherExpired {},
+
+    #[error("Mint voucher {nonce} already used")]
+    VoucherUsed { nonce: u64 },
+
+    #[error("{address} is listed more than once")]
+    DuplicateMember { address: String },
+
+    #[error("Whitelist tier {tier} does not exist")]
+    UnknownTier { tier: String },
+
+    #[error("Whitelist tier {tier} is listed more than once")]
+    DuplicateTier { tier: String },
+
+    #[error("Whitelist tier {tier} is still assigned to members")]
+    TierInUse { tier: String },
+
+    #[error("Payee shares must add up to 10000 basis points")]
+    InvalidPayees {},
+
+    #[error("No payees to distribute to")]
+    NoPayees {},
+
+    #[error("Revenue is split among payees with Distribute")]
+    PayeesConfigured {},
+
+    #[error("Platform fee can't exceed 10000 basis points")]
+    InvalidPlatformFee {},
+
     #[error("Minting period expired")]
     MintExpired {},
 
@@ -33,12 +100,51 @@ pub enum ContractError {
     #[error("Error parsing source metadata or conflicting supply parameters")]
     SourceMetadata {},
 
+    #[error("Supply can't be lower than the {minted} tokens minted")]
+    SupplyBelowMinted { minted: u64 },
+
+    #[error("Prices can't change during an active phase unless forced")]
+    PriceLocked {},
+
+    #[error("{field} can't change once minting has begun")]
+    ConfigLocked { field: String },
+
     #[error("Whitelist allowance exceeded")]
     WhitelistAllowance { minted: u64 },
 
+    #[error("Reserved allocation exceeded")]
+    ReservedAllowance { minted: u64 },
+
     #[error("Must be whitelisted to mint before public minting")]
     NotWhitelisted {},
 
+    #[error("Insufficient funds: required {required}, sent {sent}")]
+    InsufficientFunds { required: Uint128, sent: Uint128 },
+
+    #[error("Insufficient balance: available {available}, requested {requested}")]
+    InsufficientBalance { available: Uint128, requested: Uint128 },
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Escrow minimum must fit in the public supply, with a deadline ahead")]
+    InvalidEscrow {},
+
+    #[error("Sale proceeds are held in escrow")]
+    EscrowLocked {},
+
+    #[error("Refunds are only available once the escrow has failed")]
+    RefundUnavailable {},
+
+    #[error("Refunded tokens must be approved for the minter to burn")]
+    BurnNotApproved {},
+
+    #[error("Unexpected funds sent in {denom}")]
+    UnexpectedFunds { denom: String },
+
+    #[error("{address} is not the cw20 minters pay with")]
+    UnknownCw20 { address: String },
+
     #[error("All tokens distributed")]
     SoldOut {},
 
@@ -49,3 +155,9 @@ pub enum ContractError {
     #[error("This entrypoint is disabled and cannot be used")]
     EntrypointDisabled {}
 }
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
+}
@@ -1,101 +1,641 @@
-use cosmwasm_std::{BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, QueryRequest, Response, to_json_binary, WasmMsg, WasmQuery};
+use cosmwasm_std::{Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, from_json, MessageInfo, QueryRequest, Response, StdResult, Storage, SubMsg, to_json_binary, to_json_string, Uint128, WasmMsg, WasmQuery};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use crate::token::{
     ExecuteMsg as Cw721ExecuteMsg, Extension, Metadata, QueryMsg as Cw721QueryMsg,
 };
-use crate::token::cw721::{ NftInfoResponse, NumTokensResponse, OwnerOfResponse };
+use crate::token::cw721::{ NftInfoResponse, OwnerOfResponse };
 
-use crate::contract::DENOM;
-use crate::msg::{EnablePublicMintMsg, EnableRevealMsg, EnableWhitelistMintMsg, InitMsg, MintMsg, RevealMsg, WhitelistApproveMsg, WhitelistRemoveMsg, WithdrawMsg};
-use crate::state::{State, WhitelistMember, STATE, PUBLIC_WHITELIST, PUBLIC_WHITELIST_COUNTER, PRIVATE_WHITELIST, PRIVATE_WHITELIST_COUNTER, Phase};
+use crate::msg::{AcceptRoleMsg, ClaimRefundMsg, ConfigUpdateMsg, Cw20PriceMsg, DistributeMsg, EnablePublicMintMsg, EnableRevealMsg, EnableWhitelistMintMsg, InitMsg, MerkleRootMsg, MintMsg, MintReservedMsg, MintVoucher, PayeeMsg, PlatformAddressMsg, PlatformFeeMsg, ReceiveMsg, RenounceOwnershipMsg, RevealMsg, SignedMintVoucher, TransferRoleMsg, WhitelistAddMsg, WhitelistApplicationsMsg, WhitelistApproveMsg, WhitelistEntry, WhitelistRemoveMsg, WithdrawMsg};
+use crate::state::{Cw20Price, ESCROWED_FEES, Escrow, EscrowStatus, FeeAmount, Payee, PlatformFee, State, TokenPayment, TIER_MEMBERS, TOKEN_PAYMENTS, WhitelistApplication, WhitelistKind, WhitelistMember, WhitelistTier, STATE, WHITELIST_APPLICATIONS, PAYEE_CLAIMED, PENDING_ARTIST, PENDING_DEPOSITS, PENDING_OWNER, MINTERS, PHASE_RAISED, UNIQUE_MINTERS, WITHDRAWN, MINTED_COUNT, PHASE_MINTED, RESERVED_MINTED, PUBLIC_WHITELIST, PRIVATE_WHITELIST, TOKEN_ID_POOL, USED_VOUCHER_NONCES, Phase};
 
+use crate::contract::BURN_REFUND_REPLY_ID;
 use crate::error::ContractError;
 
 pub fn execute_mint(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: MintMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let phase = state.current_phase(env.block.time);
+    let quantity = msg.quantity.unwrap_or(1);
+    let price = mint_price(deps.storage, &state, &phase, &info.sender, &msg)?;
+
+    let (res, token_ids) = mint_tokens(deps.branch(), &env, &state, &phase, &info.sender, &msg)?;
+
+    // User must send funds equal to (or, higher than) the
+    // minting price of the current phase, for every token
+    let required_payment = Coin {
+        denom: state.denom.clone(),
+        amount: price.checked_mul(Uint128::from(quantity))?,
+    };
+    let payment = required_payment.amount;
+    let refund = check_sent_required_payment(&info.funds, Some(required_payment))?;
+
+    // Platform takes its fee out of the payment
+    let (mut res, fee) = charge_platform_fee(res, deps.storage, &state, &None, payment, quantity)?;
+    let raised = payment - fee;
+
+    record_raised(deps.storage, &phase, &state.denom, raised)?;
+    if state.escrow.is_some() {
+        record_token_payments(deps.storage, &phase, &token_ids, None, raised, fee)?;
+    }
+
+    // Send back anything paid above the minting price
+    if !refund.is_zero() {
+        res = res
+            .add_attribute("refund", refund.to_string())
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin {
+                    denom: state.denom,
+                    amount: refund,
+                }],
+            });
+    }
+
+    Ok(res)
+}
+
+/// Cw20 `Receive` hook. Mints when the tokens are sent by the
+/// cw20 contract set in `State.cw20_price`, for at least the
+/// configured price of every token minted
+pub fn execute_receive(
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    _msg: MintMsg,
+    wrapper: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
+    let phase = state.current_phase(env.block.time);
+    let minter = deps.api.addr_validate(&wrapper.sender)?;
+
+    // Only the configured cw20 is accepted as payment
+    let cw20_price = match state.cw20_price.clone() {
+        Some(cw20_price) if cw20_price.address == info.sender => cw20_price,
+        _ => {
+            return Err(ContractError::UnexpectedFunds {
+                denom: info.sender.to_string(),
+            })
+        }
+    };
+
+    let msg: ReceiveMsg = from_json(&wrapper.msg)?;
+    match msg {
+        ReceiveMsg::Mint(msg) => {
+            // Voucher prices are in the native denom
+            if msg.voucher.is_some() {
+                return Err(ContractError::InvalidVoucher {});
+            }
+
+            let quantity = msg.quantity.unwrap_or(1);
+            let required = cw20_price.amount.checked_mul(Uint128::from(quantity))?;
+            if wrapper.amount < required {
+                return Err(ContractError::InsufficientFunds {
+                    required,
+                    sent: wrapper.amount,
+                });
+            }
+
+            let (res, token_ids) = mint_tokens(deps.branch(), &env, &state, &phase, &minter, &msg)?;
+            // Platform takes its fee out of the payment
+            let cw20 = Some(cw20_price.address.clone());
+            let (mut res, fee) = charge_platform_fee(res, deps.storage, &state, &cw20, required, quantity)?;
+            let raised = required - fee;
+            record_raised(deps.storage, &phase, cw20_price.address.as_str(), raised)?;
+            if state.escrow.is_some() {
+                record_token_payments(deps.storage, &phase, &token_ids, cw20, raised, fee)?;
+            }
+
+            // Send back anything paid above the minting price
+            let refund = wrapper.amount - required;
+            if !refund.is_zero() {
+                let transfer_msg = Cw20ExecuteMsg::Transfer {
+                    recipient: minter.to_string(),
+                    amount: refund,
+                };
+                res = res
+                    .add_attribute("refund", refund.to_string())
+                    .add_message(WasmMsg::Execute {
+                        contract_addr: cw20_price.address.into(),
+                        msg: to_json_binary(&transfer_msg)?,
+                        funds: vec![],
+                    });
+            }
+
+            Ok(res)
+        }
+    }
+}
+
+// Takes the platform fee out of `payment`, in the native
+// denom or `cw20`, returning it. While the sale has an escrow
+// the fee is held with the payment, so refunds are in full
+fn charge_platform_fee(
+    mut res: Response,
+    storage: &mut dyn Storage,
+    state: &State,
+    cw20: &Option<Addr>,
+    payment: Uint128,
+    quantity: u64,
+) -> StdResult<(Response, Uint128)> {
+    let platform_fee = match &state.platform_fee {
+        Some(platform_fee) => platform_fee,
+        None => return Ok((res, Uint128::zero())),
+    };
+    let fee = platform_fee.fee(payment, quantity);
+    if fee.is_zero() {
+        return Ok((res, fee));
+    }
+
+    res = res.add_attribute("platform_fee", fee.to_string());
+    if state.escrow.is_some() {
+        ESCROWED_FEES.update(storage, &asset_key(state, cw20), |held| -> StdResult<_> {
+            Ok(held.unwrap_or_default() + fee)
+        })?;
+    } else {
+        res = res.add_message(transfer_msg(state, cw20, &platform_fee.address, fee)?);
+    }
+    Ok((res, fee))
+}
+
+// Sends the platform the fees held in escrow for the native
+// denom or `cw20`, once the escrow is released
+fn release_escrowed_fees(
+    res: Response,
+    storage: &mut dyn Storage,
+    state: &State,
+    cw20: &Option<Addr>,
+) -> StdResult<Response> {
+    let asset = asset_key(state, cw20);
+    let fee = ESCROWED_FEES.may_load(storage, &asset)?.unwrap_or_default();
+    let platform_fee = match &state.platform_fee {
+        Some(platform_fee) if !fee.is_zero() => platform_fee,
+        _ => return Ok(res),
+    };
+    ESCROWED_FEES.remove(storage, &asset);
+    Ok(res
+        .add_attribute("platform_fee", fee.to_string())
+        .add_message(transfer_msg(state, cw20, &platform_fee.address, fee)?))
+}
+
+// Mints `msg.quantity` tokens to `owner` after enforcing the
+// phase and whitelist rules, returning the minted token ids.
+// Payment is checked by the caller
+fn mint_tokens(
+    deps: DepsMut,
+    env: &Env,
+    state: &State,
+    phase: &Phase,
+    owner: &Addr,
+    msg: &MintMsg,
+) -> Result<(Response, Vec<u64>), ContractError> {
+    let quantity = msg.quantity.unwrap_or(1);
+
+    // At least one token must be minted
+    if quantity == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
 
     // Contract must be initialized
-    if state.phase.is_disabled() {
+    if phase.is_disabled() {
         return Err(ContractError::Unauthorized {});
     }
 
-    // Minting must not be expired
-    if state.phase.is_reveal() {
+    if state.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    // Minting must not be expired, nor the escrow failed
+    if phase.is_reveal() || escrow_status(deps.storage, state, env)? == Some(EscrowStatus::Failed) {
         return Err(ContractError::MintExpired {});
     }
 
-    // Enforce white list permissions
-    if state.phase.is_public_whitelist() {
-        // Returns an error directly if not in whitelist
-        let whitelist_member = PUBLIC_WHITELIST.load(deps.storage, &info.sender)?;
-        // Returns an error if whitelisting disabled for this sender
-        if !whitelist_member.whitelisted {
-            return Err(ContractError::NotWhitelisted {});
+    // A signed voucher stands in for the whitelists, otherwise
+    // enforce white list permissions
+    if let Some(voucher) = &msg.voucher {
+        check_voucher(deps.api, deps.storage, env, state, phase, owner, voucher, quantity)?;
+    } else {
+        if phase.is_public_whitelist() {
+            check_whitelist(
+                deps.storage,
+                WhitelistKind::Public,
+                &state.public_whitelist_merkle_root,
+                state.public_whitelist_allowance,
+                owner,
+                msg,
+            )?;
+        }
+
+        if phase.is_private_whitelist() {
+            check_whitelist(
+                deps.storage,
+                WhitelistKind::Private,
+                &state.private_whitelist_merkle_root,
+                state.private_whitelist_allowance,
+                owner,
+                msg,
+            )?;
         }
+    }
+
+    // Tokens minted so far, reserved ones included
+    let minted_count = MINTED_COUNT.may_load(deps.storage)?.unwrap_or_default();
+
+    // Fail if minting would exceed capacity, or use up the
+    // tokens held back for reserved and founders mints
+    let reserved = RESERVED_MINTED.may_load(deps.storage)?.unwrap_or_default();
+    let public_minted = minted_count.saturating_sub(reserved.reserved + reserved.founders);
+    match public_minted.checked_add(quantity) {
+        Some(total) if total <= state.public_supply() => {}
+        _ => return Err(ContractError::SoldOut {}),
+    }
+
+    MINTED_COUNT.save(deps.storage, &(minted_count + quantity))?;
+    PHASE_MINTED.update(deps.storage, phase.as_str(), |minted| -> StdResult<u64> {
+        Ok(minted.unwrap_or_default() + quantity)
+    })?;
+
+    // Count each address the first time it mints
+    if !MINTERS.has(deps.storage, owner) {
+        MINTERS.save(deps.storage, owner, &true)?;
+        let unique_minters = UNIQUE_MINTERS.may_load(deps.storage)?.unwrap_or_default();
+        UNIQUE_MINTERS.save(deps.storage, &(unique_minters + 1))?;
+    }
+
+    let token_ids = next_token_ids(deps.storage, env, state, owner, minted_count, quantity)?;
+
+    let res = Response::new()
+        .add_attribute("action", "mint")
+        .add_attribute("owner", owner.to_string())
+        .add_attribute("quantity", quantity.to_string());
+
+    let res = add_mint_msgs(res, state, owner, token_ids.clone())?;
+    Ok((res, token_ids))
+}
 
-        PUBLIC_WHITELIST_COUNTER.update(deps.storage, &info.sender, |minted| match minted {
-            Some(minted) if minted >= state.public_whitelist_allowance => {
-                Err(ContractError::WhitelistAllowance { minted })
+// Checks that `owner` is on a whitelist, either stored in
+// `list` or proven against its merkle root, and counts
+// the minted tokens against its allowance
+fn check_whitelist(
+    storage: &mut dyn Storage,
+    list: WhitelistKind,
+    merkle_root: &Option<String>,
+    default_allowance: u64,
+    owner: &Addr,
+    msg: &MintMsg,
+) -> Result<(), ContractError> {
+    let quantity = msg.quantity.unwrap_or(1);
+
+    // Stored entry, if any, is also counted in the list stats
+    let whitelist_member = list.members().may_load(storage, owner)?;
+    let allowance = match (merkle_root, &msg.proof) {
+        (Some(merkle_root), Some(proof)) => {
+            // Returns an error if the proof does not match the root
+            verify_merkle_proof(merkle_root, proof, owner, msg.allowance)?;
+            msg.allowance.unwrap_or(default_allowance)
+        }
+        _ => {
+            // Returns an error directly if not in whitelist
+            let whitelist_member = whitelist_member.as_ref().ok_or(ContractError::NotWhitelisted {})?;
+            // Returns an error if whitelisting disabled for this sender
+            if !whitelist_member.whitelisted {
+                return Err(ContractError::NotWhitelisted {});
             }
-            _ => Ok(minted.unwrap_or(0) + 1),
-        })?;
+            whitelist_member.allowance.unwrap_or(default_allowance)
+        }
+    };
+
+    // Whole batch must fit in the remaining allowance
+    let counter = list.counter();
+    let minted = counter.may_load(storage, owner)?.unwrap_or_default();
+    let new_minted = match minted.checked_add(quantity) {
+        Some(total) if total <= allowance => total,
+        _ => return Err(ContractError::WhitelistAllowance { minted }),
+    };
+    counter.save(storage, owner, &new_minted)?;
+
+    let mut stats = list.stats().may_load(storage)?.unwrap_or_default();
+    stats.total_minted += quantity;
+    if let Some(whitelist_member) = &whitelist_member {
+        if !whitelist_member.is_exhausted(minted, default_allowance)
+            && whitelist_member.is_exhausted(new_minted, default_allowance)
+        {
+            stats.exhausted_count += 1;
+        }
+    }
+    list.stats().save(storage, &stats)?;
+    Ok(())
+}
+
+// Checks that `voucher` was signed by `State.voucher_signer`
+// for `owner` in the current phase, and marks its nonce as
+// used so it can't be replayed
+#[allow(clippy::too_many_arguments)]
+fn check_voucher(
+    api: &dyn Api,
+    storage: &mut dyn Storage,
+    env: &Env,
+    state: &State,
+    phase: &Phase,
+    owner: &Addr,
+    voucher: &SignedMintVoucher,
+    quantity: u64,
+) -> Result<(), ContractError> {
+    let signer = state.voucher_signer.as_ref().ok_or(ContractError::InvalidVoucher {})?;
+
+    // Signature covers the sha256 of the json encoded voucher
+    let hash = Sha256::digest(to_json_binary(&voucher.voucher)?.as_slice());
+    if !api.secp256k1_verify(&hash, &voucher.signature, signer)? {
+        return Err(ContractError::InvalidVoucher {});
+    }
+
+    let MintVoucher {
+        contract,
+        chain_id,
+        address,
+        phase: voucher_phase,
+        max_quantity,
+        expires,
+        nonce,
+        ..
+    } = &voucher.voucher;
+    if *contract != env.contract.address || *chain_id != env.block.chain_id {
+        return Err(ContractError::InvalidVoucher {});
+    }
+    if address != owner || voucher_phase != phase || quantity > *max_quantity {
+        return Err(ContractError::InvalidVoucher {});
+    }
+    if env.block.time >= *expires {
+        return Err(ContractError::VoucherExpired {});
+    }
+
+    // Each voucher can be redeemed once
+    if USED_VOUCHER_NONCES.has(storage, *nonce) {
+        return Err(ContractError::VoucherUsed { nonce: *nonce });
+    }
+    USED_VOUCHER_NONCES.save(storage, *nonce, &true)?;
+    Ok(())
+}
+
+// Native price per token paid by `owner` in `phase`. Vouchers
+// carry their own price, and whitelist members in a tier pay
+// the tier price
+pub fn mint_price(
+    storage: &dyn Storage,
+    state: &State,
+    phase: &Phase,
+    owner: &Addr,
+    msg: &MintMsg,
+) -> StdResult<Uint128> {
+    if let Some(voucher) = &msg.voucher {
+        return Ok(voucher.voucher.price);
+    }
+
+    let whitelist_member = if phase.is_private_whitelist() {
+        PRIVATE_WHITELIST.may_load(storage, owner)?
+    } else if phase.is_public_whitelist() {
+        PUBLIC_WHITELIST.may_load(storage, owner)?
+    } else {
+        None
+    };
+    let tier_price = whitelist_member
+        .and_then(|member| member.tier)
+        .and_then(|tier| state.tier_price(&tier));
+    Ok(tier_price.unwrap_or_else(|| state.phase_price(phase)))
+}
+
+// Adds mint revenue kept by the contract to the running
+// total of `phase`, by native denom or cw20 address
+fn record_raised(
+    storage: &mut dyn Storage,
+    phase: &Phase,
+    asset: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    PHASE_RAISED.update(storage, (phase.as_str(), asset), |raised| -> StdResult<_> {
+        Ok(raised.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+// Splits what the sale kept, and the platform fee held, for
+// `token_ids` evenly between them, so each can be refunded in
+// full if the escrow fails. Rounding dust goes to the first
+// token
+fn record_token_payments(
+    storage: &mut dyn Storage,
+    phase: &Phase,
+    token_ids: &[u64],
+    cw20: Option<Addr>,
+    raised: Uint128,
+    fee: Uint128,
+) -> StdResult<()> {
+    let quantity = Uint128::from(token_ids.len() as u64);
+    let split = |amount: Uint128| -> StdResult<(Uint128, Uint128)> {
+        let per_token = amount.checked_div(quantity)?;
+        Ok((per_token, amount - per_token * quantity))
+    };
+    let (raised_per_token, mut raised_dust) = split(raised)?;
+    let (fee_per_token, mut fee_dust) = split(fee)?;
+    for token_id in token_ids {
+        let payment = TokenPayment {
+            phase: phase.clone(),
+            cw20: cw20.clone(),
+            amount: raised_per_token + raised_dust,
+            fee: fee_per_token + fee_dust,
+        };
+        TOKEN_PAYMENTS.save(storage, &token_id.to_string(), &payment)?;
+        raised_dust = Uint128::zero();
+        fee_dust = Uint128::zero();
+    }
+    Ok(())
+}
+
+// Status of the crowdfund escrow, if the sale has one. Only
+// public mints count towards its minimum
+pub fn escrow_status(
+    storage: &dyn Storage,
+    state: &State,
+    env: &Env,
+) -> StdResult<Option<EscrowStatus>> {
+    let escrow = match &state.escrow {
+        Some(escrow) => escrow,
+        None => return Ok(None),
+    };
+    let minted_count = MINTED_COUNT.may_load(storage)?.unwrap_or_default();
+    let reserved = RESERVED_MINTED.may_load(storage)?.unwrap_or_default();
+    let sold = minted_count.saturating_sub(reserved.reserved + reserved.founders);
+    Ok(Some(escrow.status(sold, env.block.time)))
+}
+
+// Revenue can only be paid out once the escrow, if any,
+// has reached its minimum
+fn check_escrow_released(storage: &dyn Storage, state: &State, env: &Env) -> Result<(), ContractError> {
+    match escrow_status(storage, state, env)? {
+        None | Some(EscrowStatus::Succeeded) => Ok(()),
+        Some(_) => Err(ContractError::EscrowLocked {}),
+    }
+}
+
+// Adds revenue paid out by Withdraw or Distribute to the
+// running total of `asset`
+fn record_withdrawn(storage: &mut dyn Storage, asset: &str, amount: Uint128) -> StdResult<()> {
+    WITHDRAWN.update(storage, asset, |withdrawn| -> StdResult<_> {
+        Ok(withdrawn.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+// Ids of the next `quantity` tokens, after `minted_count` tokens
+// have been minted. Ids are sequential, unless the contract was
+// created with `shuffle_token_ids`; then each one is drawn from
+// the pool of unassigned ids in 1..=supply
+fn next_token_ids(
+    storage: &mut dyn Storage,
+    env: &Env,
+    state: &State,
+    owner: &Addr,
+    minted_count: u64,
+    quantity: u64,
+) -> Result<Vec<u64>, ContractError> {
+    if !state.shuffle_token_ids {
+        return Ok((minted_count + 1..=minted_count + quantity).collect());
     }
 
-    if state.phase.is_private_whitelist() {
-        // Returns an error directly if not in whitelist
-        let whitelist_member = PRIVATE_WHITELIST.load(deps.storage, &info.sender)?;
-        // Returns an error if whitelisting disabled for this sender
-        if !whitelist_member.whitelisted {
-            return Err(ContractError::NotWhitelisted {});
+    let mut token_ids = Vec::with_capacity(quantity as usize);
+    for minted in minted_count..minted_count + quantity {
+        // TOKEN_ID_POOL is a sparse array of the unassigned ids,
+        // where a missing entry at `index` holds id `index + 1`
+        let remaining = state.supply - minted;
+        let index = random_index(env, owner, minted) % remaining;
+        let last = remaining - 1;
+
+        let token_id = TOKEN_ID_POOL.may_load(storage, index)?.unwrap_or(index + 1);
+        // Swap the last unassigned id into the drawn slot
+        if index != last {
+            let last_id = TOKEN_ID_POOL.may_load(storage, last)?.unwrap_or(last + 1);
+            TOKEN_ID_POOL.save(storage, index, &last_id)?;
         }
+        TOKEN_ID_POOL.remove(storage, last);
 
-        PRIVATE_WHITELIST_COUNTER.update(deps.storage, &info.sender, |minted| match minted {
-            Some(minted) if minted >= state.private_whitelist_allowance => {
-                Err(ContractError::WhitelistAllowance { minted })
-            }
-            _ => Ok(minted.unwrap_or(0) + 1),
-        })?;
+        token_ids.push(token_id);
     }
+    Ok(token_ids)
+}
 
-    // Get numeric token_id
-    let query_msg: crate::token::QueryMsg<Extension> = Cw721QueryMsg::NumTokens {};
-    let query_req = QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: state.cw721.clone().into(),
-        msg: to_json_binary(&query_msg).unwrap(),
-    });
-    let query_resp: NumTokensResponse = deps.querier.query(&query_req)?;
-    let token_id = query_resp.count + 1;
+// Pseudo-random number seeded from block and tx data, the
+// minter and the number of tokens minted so far
+fn random_index(env: &Env, owner: &Addr, minted: u64) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    if let Some(transaction) = &env.transaction {
+        hasher.update(transaction.index.to_be_bytes());
+    }
+    hasher.update(owner.as_bytes());
+    hasher.update(minted.to_be_bytes());
+    let hash = hasher.finalize();
 
-    // Fail if minting would exceed capacity
-    if token_id > state.supply {
-        return Err(ContractError::SoldOut {});
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+// Adds messages minting empty NFTs with no metadata to `owner`
+fn add_mint_msgs(
+    mut res: Response,
+    state: &State,
+    owner: &Addr,
+    token_ids: impl IntoIterator<Item = u64>,
+) -> Result<Response, ContractError> {
+    for token_id in token_ids {
+        let mint_msg: crate::token::ExecuteMsg = Cw721ExecuteMsg::Mint{{token_mint_params}};
+        let mint_resp: CosmosMsg = WasmMsg::Execute {
+            contract_addr: state.cw721.clone().into(),
+            msg: to_json_binary(&mint_msg)?,
+            funds: vec![],
+        }
+        .into();
+
+        res = res
+            .add_attribute("token_id", token_id.to_string())
+            .add_message(mint_resp);
     }
 
-    // User must send funds equal to (or, higher than) minting price
-    let required_payment = Coin {
-        denom: DENOM.to_string(),
-        amount: state.price,
+    Ok(res)
+}
+
+// Can only be called by admin. Mints from the reserved or
+// founders allocation at any phase, without enforcing the
+// minting price
+pub fn execute_mint_reserved(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: MintReservedMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if msg.quantity == 0 {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let recipients = if msg.recipients.is_empty() {
+        vec![state.artist.clone()]
+    } else {
+        msg.recipients
+            .iter()
+            .map(|recipient| validate_address(deps.api, recipient))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let total = msg
+        .quantity
+        .checked_mul(recipients.len() as u64)
+        .ok_or(ContractError::InvalidInput {})?;
+
+    // Minted tokens must fit in the allocation
+    let mut reserved = RESERVED_MINTED.may_load(deps.storage)?.unwrap_or_default();
+    let (minted, allocation) = if msg.founders {
+        (&mut reserved.founders, state.total_reserved_founders)
+    } else {
+        (&mut reserved.reserved, state.total_reserved)
     };
-    check_sent_required_payment(&info.funds, Some(required_payment))?;
+    match minted.checked_add(total) {
+        Some(new_minted) if new_minted <= allocation => *minted = new_minted,
+        _ => return Err(ContractError::ReservedAllowance { minted: *minted }),
+    }
+    RESERVED_MINTED.save(deps.storage, &reserved)?;
 
-    // Mint empty NFT with no metadata
-    let mint_msg: crate::token::ExecuteMsg = Cw721ExecuteMsg::Mint{{token_mint_params}};
-    let mint_resp: CosmosMsg = WasmMsg::Execute {
-        contract_addr: state.cw721.into(),
-        msg: to_json_binary(&mint_msg)?,
-        funds: vec![],
+    // Fail if minting would exceed capacity
+    let minted_count = MINTED_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    let new_count = match minted_count.checked_add(total) {
+        Some(new_count) if new_count <= state.supply => new_count,
+        _ => return Err(ContractError::SoldOut {}),
+    };
+    MINTED_COUNT.save(deps.storage, &new_count)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "mint_reserved")
+        .add_attribute("founders", msg.founders.to_string())
+        .add_attribute("quantity", total.to_string());
+
+    let mut minted = minted_count;
+    for recipient in recipients.iter() {
+        let token_ids =
+            next_token_ids(deps.storage, &env, &state, recipient, minted, msg.quantity)?;
+        res = add_mint_msgs(res, &state, recipient, token_ids)?;
+        minted += msg.quantity;
     }
-    .into();
 
-    Ok(Response::new()
-        .add_attribute("action", "mint")
-        .add_attribute("token_id", token_id.to_string())
-        .add_attribute("owner", info.sender.to_string())
-        .add_message(mint_resp))
+    Ok(res)
 }
 
 /// Public minting must be enabled manually.
@@ -103,19 +643,20 @@ pub fn execute_mint(
 /// the contract admin account.
 pub fn execute_enable_normal_whitelist(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     _msg: EnableWhitelistMintMsg,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
+    let phase = state.current_phase(env.block.time);
 
     // Contract must be initialized
-    if state.phase.is_disabled() {
+    if phase.is_disabled() {
         return Err(ContractError::Unauthorized {});
     }
 
     // Public minting must not be enabled already
-    if state.phase.is_public_whitelist() {
+    if phase.is_public_whitelist() {
         return Err(ContractError::PublicWhitelistMintEnabled {});
     }
 
@@ -135,19 +676,20 @@ pub fn execute_enable_normal_whitelist(
 /// the contract admin account.
 pub fn execute_enable_public_mint(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     _msg: EnablePublicMintMsg,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
+    let phase = state.current_phase(env.block.time);
 
     // Contract must be initialized
-    if state.phase.is_disabled() {
+    if phase.is_disabled() {
         return Err(ContractError::Unauthorized {});
     }
 
     // Public minting must not be enabled already
-    if state.phase.is_public_mint() {
+    if phase.is_public_mint() {
         return Err(ContractError::PublicMintEnabled {});
     }
 
@@ -166,7 +708,7 @@ pub fn execute_enable_public_mint(
 /// can only be broadcast by the contract admin account.
 pub fn execute_enable_reveal(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     _msg: EnableRevealMsg,
 ) -> Result<Response, ContractError> {
@@ -178,14 +720,15 @@ pub fn execute_enable_reveal(
 
     // Expand to this if updatable
     let mut state = STATE.load(deps.storage)?;
+    let phase = state.current_phase(env.block.time);
 
     // Contract must be initialized
-    if state.phase.is_disabled() {
+    if phase.is_disabled() {
         return Err(ContractError::Unauthorized {});
     }
 
     // Must be in pre-reveal phase
-    if state.phase.is_reveal() {
+    if phase.is_reveal() {
         return Err(ContractError::RevealEnabled {});
     }
 
@@ -206,13 +749,18 @@ pub fn execute_reveal(
     info: MessageInfo,
     msg: RevealMsg,
 ) -> Result<Response, ContractError> {
+    if STATE.load(deps.storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+
     {{execute_reveal}}
 }
 
-// Can only be called by artist
+// Can only be called by artist. Withdraws the native
+// denom, or the balance of a cw20 if one is given
 pub fn execute_withdraw_funds(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: WithdrawMsg,
 ) -> Result<Response, ContractError> {
@@ -223,29 +771,230 @@ pub fn execute_withdraw_funds(
         return Err(ContractError::Unauthorized {});
     }
 
-    let bank_transfer_msg = BankMsg::Send {
-        to_address: info.sender.into(),
-        amount: ([Coin {
-            denom: DENOM.to_string(),
-            amount,
-        }])
-        .to_vec(),
-    };
+    // Payees are paid with Distribute instead
+    if !state.payees.is_empty() {
+        return Err(ContractError::PayeesConfigured {});
+    }
 
-    let bank_transfer: CosmosMsg = cosmwasm_std::CosmosMsg::Bank(bank_transfer_msg);
+    if state.paused {
+        return Err(ContractError::Paused {});
+    }
 
-    Ok(Response::new()
+    check_escrow_released(deps.storage, &state, &env)?;
+
+    let available = distributable_balance(deps.as_ref(), &env, &state, &msg.cw20)?;
+    if amount > available {
+        return Err(ContractError::InsufficientBalance {
+            available,
+            requested: amount,
+        });
+    }
+    record_withdrawn(deps.storage, &asset_key(&state, &msg.cw20), amount)?;
+
+    let transfer = transfer_msg(&state, &msg.cw20, &info.sender, amount)?;
+    let res = Response::new()
         .add_attribute("action", "withdraw")
         .add_attribute("amount", amount.to_string())
-        .add_message(bank_transfer))
+        .add_message(transfer);
+
+    Ok(release_escrowed_fees(res, deps.storage, &state, &msg.cw20)?)
+}
+
+// Anyone can split the contract balance among the payees,
+// pro rata to their shares. Rounding dust is left for the
+// next distribution
+pub fn execute_distribute(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: DistributeMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if state.payees.is_empty() {
+        return Err(ContractError::NoPayees {});
+    }
+
+    if state.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    check_escrow_released(deps.storage, &state, &env)?;
+
+    // Anyone can distribute, so only the configured cw20 may
+    // be queried and paid out
+    if let Some(cw20) = &msg.cw20 {
+        if state.cw20_price.as_ref().map(|price| &price.address) != Some(cw20) {
+            return Err(ContractError::UnknownCw20 { address: cw20.to_string() });
+        }
+    }
+
+    let balance = distributable_balance(deps.as_ref(), &env, &state, &msg.cw20)?;
+    let asset = asset_key(&state, &msg.cw20);
+    let mut distributed = Uint128::zero();
+
+    let mut res = Response::new()
+        .add_attribute("action", "distribute")
+        .add_attribute("amount", balance.to_string());
+    for payee in state.payees.iter() {
+        let amount = balance.multiply_ratio(payee.share_bps, 10000_u128);
+        if amount.is_zero() {
+            continue;
+        }
+        PAYEE_CLAIMED.update(deps.storage, (&payee.address, asset.as_str()), |claimed| -> StdResult<_> {
+            Ok(claimed.unwrap_or_default() + amount)
+        })?;
+        distributed += amount;
+        res = res.add_message(transfer_msg(&state, &msg.cw20, &payee.address, amount)?);
+    }
+    record_withdrawn(deps.storage, &asset, distributed)?;
+
+    Ok(release_escrowed_fees(res, deps.storage, &state, &msg.cw20)?)
+}
+
+// Balance of the native denom, or of `cw20`, that can be
+// paid out. Deposits of pending applications, and platform
+// fees held in escrow, are held back
+pub fn distributable_balance(
+    deps: Deps,
+    env: &Env,
+    state: &State,
+    cw20: &Option<Addr>,
+) -> StdResult<Uint128> {
+    let held_fees = ESCROWED_FEES.may_load(deps.storage, &asset_key(state, cw20))?.unwrap_or_default();
+    match cw20 {
+        Some(cw20) => {
+            let balance: Cw20BalanceResponse = deps.querier.query_wasm_smart(
+                cw20,
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            Ok(balance.balance.saturating_sub(held_fees))
+        }
+        None => {
+            let balance = deps.querier.query_balance(&env.contract.address, &state.denom)?;
+            let pending = PENDING_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+            Ok(balance.amount.saturating_sub(pending + held_fees))
+        }
+    }
+}
+
+// Key of the paid out asset in `PAYEE_CLAIMED`
+pub fn asset_key(state: &State, cw20: &Option<Addr>) -> String {
+    match cw20 {
+        Some(cw20) => cw20.to_string(),
+        None => state.denom.clone(),
+    }
+}
+
+// Sends `amount` of the native denom, or of `cw20`, to `recipient`
+fn transfer_msg(
+    state: &State,
+    cw20: &Option<Addr>,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    let transfer: CosmosMsg = match cw20 {
+        Some(cw20) => {
+            let transfer_msg = Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            };
+            WasmMsg::Execute {
+                contract_addr: cw20.to_string(),
+                msg: to_json_binary(&transfer_msg)?,
+                funds: vec![],
+            }
+            .into()
+        }
+        None => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: state.denom.clone(),
+                amount,
+            }],
+        }
+        .into(),
+    };
+    Ok(transfer)
 }
 
-// Can only be called by admin
+// Once the escrow has failed, minters get back what they
+// paid for their tokens, which are burned. The minter must
+// be approved to burn them on the cw721 contract
+pub fn execute_claim_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ClaimRefundMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if escrow_status(deps.storage, &state, &env)? != Some(EscrowStatus::Failed) {
+        return Err(ContractError::RefundUnavailable {});
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "claim_refund")
+        .add_attribute("owner", info.sender.to_string());
+    for token_id in msg.token_ids.iter() {
+        // Only the current owner of the token is refunded
+        let query_msg: crate::token::QueryMsg<Extension> = Cw721QueryMsg::OwnerOf {
+            token_id: token_id.clone(),
+            include_expired: None,
+        };
+        let query_req = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: state.cw721.clone().into(),
+            msg: to_json_binary(&query_msg)?,
+        });
+        let owner_resp: OwnerOfResponse = deps.querier.query(&query_req)?;
+        if owner_resp.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // Returns an error directly if no payment is held for the token
+        let payment = TOKEN_PAYMENTS.load(deps.storage, token_id)?;
+        TOKEN_PAYMENTS.remove(deps.storage, token_id);
+
+        // Refunded payments no longer count as raised, nor as
+        // fees owed to the platform
+        let asset = asset_key(&state, &payment.cw20);
+        PHASE_RAISED.update(deps.storage, (payment.phase.as_str(), asset.as_str()), |raised| -> StdResult<_> {
+            Ok(raised.unwrap_or_default().saturating_sub(payment.amount))
+        })?;
+        ESCROWED_FEES.update(deps.storage, &asset, |held| -> StdResult<_> {
+            Ok(held.unwrap_or_default().saturating_sub(payment.fee))
+        })?;
+
+        let burn_msg: crate::token::ExecuteMsg = Cw721ExecuteMsg::Burn {
+            token_id: token_id.clone(),
+        };
+        let burn = WasmMsg::Execute {
+            contract_addr: state.cw721.to_string(),
+            msg: to_json_binary(&burn_msg)?,
+            funds: vec![],
+        };
+        res = res
+            .add_attribute("token_id", token_id.clone())
+            .add_submessage(SubMsg::reply_on_error(burn, BURN_REFUND_REPLY_ID));
+        let refund = payment.amount + payment.fee;
+        if !refund.is_zero() {
+            res = res.add_message(transfer_msg(&state, &payment.cw20, &info.sender, refund)?);
+        }
+    }
+
+    Ok(res)
+}
+
+// Can only be called by admin. Fields left unset keep their
+// current value, and each changed field is reported as an
+// attribute
 pub fn execute_update_config(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    config_update: State,
+    msg: ConfigUpdateMsg,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
 
@@ -253,26 +1002,192 @@ pub fn execute_update_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    STATE.save(deps.storage, &config_update)?;
+    update_config(deps, &env, state, msg)
+}
+
+// Applies `msg` over `state`, enforcing the same rules for
+// UpdateConfig and migrations
+pub fn update_config(
+    deps: DepsMut,
+    env: &Env,
+    state: State,
+    msg: ConfigUpdateMsg,
+) -> Result<Response, ContractError> {
+    let mut config = state.clone();
+    let mut res = Response::new().add_attribute("action", "update_config");
+    let cw721 = msg.cw721.map(|cw721| validate_address(deps.api, &cw721)).transpose()?;
+    res = update_field(res, "cw721", &mut config.cw721, cw721, plain)?;
+    res = update_field(res, "supply", &mut config.supply, msg.supply, plain)?;
+    res = update_field(res, "schedule", &mut config.schedule, msg.schedule, json)?;
+    res = update_field(res, "total_reserved", &mut config.total_reserved, msg.total_reserved, plain)?;
+    res = update_field(res, "total_reserved_founders", &mut config.total_reserved_founders, msg.total_reserved_founders, plain)?;
+    res = update_field(res, "shuffle_token_ids", &mut config.shuffle_token_ids, msg.shuffle_token_ids, plain)?;
+    res = update_field(res, "private_whitelist_allowance", &mut config.private_whitelist_allowance, msg.private_whitelist_allowance, plain)?;
+    res = update_field(res, "public_whitelist_allowance", &mut config.public_whitelist_allowance, msg.public_whitelist_allowance, plain)?;
+    res = update_field(res, "private_whitelist_price", &mut config.private_whitelist_price, msg.private_whitelist_price, plain)?;
+    res = update_field(res, "public_whitelist_price", &mut config.public_whitelist_price, msg.public_whitelist_price, plain)?;
+    res = update_field(res, "whitelist_tiers", &mut config.whitelist_tiers, msg.whitelist_tiers, json)?;
+    res = update_field(res, "whitelist_deposit", &mut config.whitelist_deposit, msg.whitelist_deposit, plain)?;
+    res = update_field(res, "price", &mut config.price, msg.price, plain)?;
+    res = update_field(res, "denom", &mut config.denom, msg.denom, plain)?;
+    let cw20_price = msg
+        .cw20_price
+        .map(|price| price.map(|price| validate_cw20_price(deps.api, price)).transpose())
+        .transpose()?;
+    res = update_field(res, "cw20_price", &mut config.cw20_price, cw20_price, json)?;
+    res = update_field(res, "name_prefix", &mut config.name_prefix, msg.name_prefix, plain)?;
+    let payees = msg.payees.map(|payees| validate_payees(deps.api, payees)).transpose()?;
+    res = update_field(res, "payees", &mut config.payees, payees, json)?;
+    res = update_field(res, "private_whitelist_merkle_root", &mut config.private_whitelist_merkle_root, msg.private_whitelist_merkle_root, optional)?;
+    res = update_field(res, "public_whitelist_merkle_root", &mut config.public_whitelist_merkle_root, msg.public_whitelist_merkle_root, optional)?;
+    res = update_field(res, "voucher_signer", &mut config.voucher_signer, msg.voucher_signer, base64)?;
+    res = update_field(res, "escrow", &mut config.escrow, msg.escrow, json)?;
+
+    if !config.schedule.is_valid() {
+        return Err(ContractError::InvalidSchedule {});
+    }
+
+    // Members priced by a tier keep it until reassigned
+    validate_whitelist_tiers(&config.whitelist_tiers)?;
+    for tier in state.whitelist_tiers.iter() {
+        if config.tier_price(&tier.name).is_none()
+            && TIER_MEMBERS.may_load(deps.storage, &tier.name)?.unwrap_or_default() > 0
+        {
+            return Err(ContractError::TierInUse { tier: tier.name.clone() });
+        }
+    }
+
+    validate_merkle_root(&config.private_whitelist_merkle_root)?;
+    validate_merkle_root(&config.public_whitelist_merkle_root)?;
+    if !config.payees_valid() {
+        return Err(ContractError::InvalidPayees {});
+    }
+    validate_fee_asset(&config.platform_fee, &config.cw20_price)?;
+    validate_escrow(&config, &state.escrow, env)?;
+
+    // Supply must cover what was minted, and the reserved
+    // allocations what was minted from them
+    let minted_count = MINTED_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    if config.supply < minted_count {
+        return Err(ContractError::SupplyBelowMinted { minted: minted_count });
+    }
+    let reserved = RESERVED_MINTED.may_load(deps.storage)?.unwrap_or_default();
+    let total_reserved = config.total_reserved.checked_add(config.total_reserved_founders);
+    if !matches!(total_reserved, Some(total_reserved) if total_reserved <= config.supply)
+        || config.total_reserved < reserved.reserved
+        || config.total_reserved_founders < reserved.founders
+    {
+        return Err(ContractError::SourceMetadata {});
+    }
+
+    // Prices are fixed while a phase is minting, unless forced
+    let price_changed = config.price != state.price
+        || config.private_whitelist_price != state.private_whitelist_price
+        || config.public_whitelist_price != state.public_whitelist_price
+        || config.whitelist_tiers != state.whitelist_tiers
+        || config.cw20_price != state.cw20_price;
+    if price_changed && state.current_phase(env.block.time).is_minting() && !msg.force.unwrap_or(false) {
+        return Err(ContractError::PriceLocked {});
+    }
+
+    // Pool of unassigned ids is sized from the supply, so the
+    // id mode and (when shuffled) supply are fixed once minting
+    // has begun. So are the collection, the payment denom and
+    // the escrow terms minters paid under
+    if minted_count > 0 {
+        let pool_changed = config.shuffle_token_ids != state.shuffle_token_ids
+            || (state.shuffle_token_ids && config.supply != state.supply);
+        let locked = [
+            ("cw721", config.cw721 != state.cw721),
+            ("denom", config.denom != state.denom),
+            ("shuffle_token_ids", pool_changed),
+            ("escrow", config.escrow != state.escrow),
+        ];
+        if let Some((field, _)) = locked.iter().find(|(_, changed)| *changed) {
+            return Err(ContractError::ConfigLocked { field: field.to_string() });
+        }
+    }
+
+    // Pending deposits are refunded in the denom they were
+    // paid in
+    let pending = PENDING_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+    if !pending.is_zero() && config.denom != state.denom {
+        return Err(ContractError::ConfigLocked { field: "denom".to_string() });
+    }
+
+    // Members are counted as exhausted as they mint, so each
+    // whitelist's default allowance is fixed once it has minted
+    let allowances = [
+        ("private_whitelist_allowance", WhitelistKind::Private),
+        ("public_whitelist_allowance", WhitelistKind::Public),
+    ];
+    for (field, list) in allowances {
+        let stats = list.stats().may_load(deps.storage)?.unwrap_or_default();
+        if stats.total_minted > 0 && list.default_allowance(&config) != list.default_allowance(&state) {
+            return Err(ContractError::ConfigLocked { field: field.to_string() });
+        }
+    }
 
-    Ok(Response::new().add_attribute("action", "update_config"))
+    STATE.save(deps.storage, &config)?;
+
+    Ok(res)
+}
+
+// Sets `field` to `value`, if given, adding an attribute to
+// `res` when it changes, with the value as given by `encode`
+fn update_field<T: PartialEq>(
+    res: Response,
+    key: &str,
+    field: &mut T,
+    value: Option<T>,
+    encode: fn(&T) -> StdResult<String>,
+) -> StdResult<Response> {
+    match value {
+        Some(value) if value != *field => {
+            let res = res.add_attribute(key, encode(&value)?);
+            *field = value;
+            Ok(res)
+        }
+        _ => Ok(res),
+    }
+}
+
+// Attribute values of scalar and string fields
+fn plain<T: ToString>(value: &T) -> StdResult<String> {
+    Ok(value.to_string())
 }
 
-// Can only be called by admin. Note that minting reserved NFTs
-// to the founder does not enforce the minting price (state.price)
+// Attribute values of optional fields, empty once cleared
+fn optional<T: ToString>(value: &Option<T>) -> StdResult<String> {
+    Ok(value.as_ref().map(T::to_string).unwrap_or_default())
+}
+
+// Attribute values of optional binary fields, as base64
+fn base64(value: &Option<Binary>) -> StdResult<String> {
+    Ok(value.as_ref().map(Binary::to_base64).unwrap_or_default())
+}
+
+// Attribute values of compound fields
+fn json<T: Serialize>(value: &T) -> StdResult<String> {
+    to_json_string(value)
+}
+
+// Can only be called by admin. Opens the private whitelist;
+// reserved NFTs are minted to the founder with MintReserved
 pub fn execute_init(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     _msg: InitMsg,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
+    let phase = state.current_phase(env.block.time);
 
     if state.owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
-    if state.phase.is_enabled() {
+    if phase.is_enabled() {
         return Err(ContractError::Initialized {});
     }
 
@@ -298,7 +1213,8 @@ pub fn execute_public_whitelist_remove(
     }
 
     for member in msg.whitelist_members.iter() {
-        PUBLIC_WHITELIST.remove(deps.storage, member);
+        let member = validate_address(deps.api, member)?;
+        WhitelistKind::Public.remove_member(deps.storage, &state, &member)?;
     }
 
     Ok(Response::new().add_attribute("action", "public_whitelist_remove"))
@@ -320,12 +1236,8 @@ pub fn execute_public_whitelist_approve(
         return Err(ContractError::Unauthorized {});
     }
 
-    for member in msg.whitelist_members.iter() {
-        let member_approval = WhitelistMember { whitelisted: true };
-        PUBLIC_WHITELIST.save(deps.storage, member, &member_approval)?;
-    }
-
-    Ok(Response::new().add_attribute("action", "public_whitelist_approve"))
+    let res = Response::new().add_attribute("action", "public_whitelist_approve");
+    approve_whitelist_entries(deps, &state, WhitelistKind::Public, msg.whitelist_members, res)
 }
 
 // Only admin can remove whitelist members
@@ -343,7 +1255,8 @@ pub fn execute_private_whitelist_remove(
     }
 
     for member in msg.whitelist_members.iter() {
-        PRIVATE_WHITELIST.remove(deps.storage, member);
+        let member = validate_address(deps.api, member)?;
+        WhitelistKind::Private.remove_member(deps.storage, &state, &member)?;
     }
 
     Ok(Response::new().add_attribute("action", "private_whitelist_remove"))
@@ -365,34 +1278,559 @@ pub fn execute_private_whitelist_approve(
         return Err(ContractError::Unauthorized {});
     }
 
-    for member in msg.whitelist_members.iter() {
-        let member_approval = WhitelistMember { whitelisted: true };
-        PRIVATE_WHITELIST.save(deps.storage, member, &member_approval)?;
+    let res = Response::new().add_attribute("action", "private_whitelist_approve");
+    approve_whitelist_entries(deps, &state, WhitelistKind::Private, msg.whitelist_members, res)
+}
+
+// Saves approved entries to `list`. Entries already on the
+// list, or repeated in the batch, are updated and reported
+// with a "duplicate" attribute
+fn approve_whitelist_entries(
+    deps: DepsMut,
+    state: &State,
+    list: WhitelistKind,
+    entries: Vec<WhitelistEntry>,
+    mut res: Response,
+) -> Result<Response, ContractError> {
+    let members = list.members();
+    let approved = entries.len();
+    for entry in entries.into_iter() {
+        let address = validate_address(deps.api, &entry.address)?;
+        if let Some(tier) = &entry.tier {
+            if state.tier_price(tier).is_none() {
+                return Err(ContractError::UnknownTier { tier: tier.clone() });
+            }
+        }
+
+        if members.has(deps.storage, &address) {
+            res = res.add_attribute("duplicate", address.to_string());
+        }
+        let member_approval = WhitelistMember {
+            whitelisted: true,
+            allowance: entry.allowance,
+            tier: entry.tier,
+        };
+        list.save_member(deps.storage, state, &address, &member_approval)?;
+    }
+
+    Ok(res.add_attribute("approved", approved.to_string()))
+}
+
+// Anyone can apply to a whitelist, paying the configured
+// deposit. Applications wait for the admin to approve or
+// reject them
+pub fn execute_whitelist_add(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: WhitelistAddMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    // Members and pending applicants can't apply again
+    if msg.list.members().has(deps.storage, &info.sender)
+        || WHITELIST_APPLICATIONS.has(deps.storage, &info.sender)
+    {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    let deposit = state.whitelist_deposit;
+    let required_payment = if deposit.is_zero() {
+        None
+    } else {
+        Some(Coin {
+            denom: state.denom.clone(),
+            amount: deposit,
+        })
+    };
+    let refund = check_sent_required_payment(&info.funds, required_payment)?;
+
+    let application = WhitelistApplication {
+        list: msg.list,
+        deposit,
+    };
+    WHITELIST_APPLICATIONS.save(deps.storage, &info.sender, &application)?;
+    let pending = PENDING_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+    PENDING_DEPOSITS.save(deps.storage, &(pending + deposit))?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "whitelist_add")
+        .add_attribute("applicant", info.sender.clone())
+        .add_attribute("deposit", deposit.to_string());
+
+    // Send back anything paid above the deposit
+    if !refund.is_zero() {
+        res = res
+            .add_attribute("refund", refund.to_string())
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin {
+                    denom: state.denom,
+                    amount: refund,
+                }],
+            });
     }
 
-    Ok(Response::new().add_attribute("action", "private_whitelist_approve"))
+    Ok(res)
+}
+
+// Only admin can approve applications. Applicants join
+// the whitelist they applied to, and their deposit is
+// kept with the mint revenue
+pub fn execute_whitelist_applications_approve(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: WhitelistApplicationsMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    // Only admin can approve members
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for applicant in msg.applicants.iter() {
+        let applicant = &validate_address(deps.api, applicant)?;
+        // Returns an error directly if no application is pending
+        let application = WHITELIST_APPLICATIONS.load(deps.storage, applicant)?;
+        // Members approved while the application was pending
+        // keep their tier and allowance
+        if !application.list.members().has(deps.storage, applicant) {
+            let member_approval = WhitelistMember {
+                whitelisted: true,
+                ..Default::default()
+            };
+            application.list.save_member(deps.storage, &state, applicant, &member_approval)?;
+        }
+        WHITELIST_APPLICATIONS.remove(deps.storage, applicant);
+        release_deposit(deps.storage, application.deposit)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "whitelist_applications_approve")
+        .add_attribute("approved", msg.applicants.len().to_string()))
+}
+
+// Only admin can reject applications. Deposits are
+// refunded to the rejected applicants
+pub fn execute_whitelist_applications_reject(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: WhitelistApplicationsMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    // Only admin can reject members
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "whitelist_applications_reject")
+        .add_attribute("rejected", msg.applicants.len().to_string());
+    for applicant in msg.applicants.iter() {
+        let applicant = &validate_address(deps.api, applicant)?;
+        // Returns an error directly if no application is pending
+        let application = WHITELIST_APPLICATIONS.load(deps.storage, applicant)?;
+        WHITELIST_APPLICATIONS.remove(deps.storage, applicant);
+        release_deposit(deps.storage, application.deposit)?;
+
+        if !application.deposit.is_zero() {
+            res = res.add_message(BankMsg::Send {
+                to_address: applicant.to_string(),
+                amount: vec![Coin {
+                    denom: state.denom.clone(),
+                    amount: application.deposit,
+                }],
+            });
+        }
+    }
+
+    Ok(res)
+}
+
+// Deposit of a reviewed application is no longer held back
+fn release_deposit(storage: &mut dyn Storage, deposit: Uint128) -> StdResult<()> {
+    let pending = PENDING_DEPOSITS.may_load(storage)?.unwrap_or_default();
+    PENDING_DEPOSITS.save(storage, &pending.saturating_sub(deposit))
 }
 
-// Helper fn to enforce minting price
+// Only admin can pause. Minting, reveals, Withdraw and
+// Distribute are halted until unpaused, in whatever phase
+// the sale is in
+pub fn execute_pause(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    set_paused(deps, info, true)
+}
+
+pub fn execute_unpause(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    set_paused(deps, info, false)
+}
+
+fn set_paused(deps: DepsMut, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    state.paused = paused;
+    STATE.save(deps.storage, &state)?;
+
+    let action = if paused { "pause" } else { "unpause" };
+    Ok(Response::new().add_attribute("action", action))
+}
+
+// Only admin can move the platform fee to another address.
+// The fee itself can't be changed once instantiated
+pub fn execute_update_platform_address(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: PlatformAddressMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    // Only admin can move the fee
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address = validate_address(deps.api, &msg.address)?;
+    let platform_fee = state.platform_fee.as_mut().ok_or(ContractError::InvalidInput {})?;
+    platform_fee.address = address.clone();
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_platform_address")
+        .add_attribute("address", address))
+}
+
+// Only admin can propose a new owner, replacing any pending
+// proposal. The role only moves once accepted
+pub fn execute_transfer_ownership(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: TransferRoleMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pending_owner = validate_address(deps.api, &msg.address)?;
+    PENDING_OWNER.save(deps.storage, &pending_owner)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer_ownership")
+        .add_attribute("pending_owner", pending_owner))
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    _msg: AcceptRoleMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    if PENDING_OWNER.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PENDING_OWNER.remove(deps.storage);
+    state.owner = info.sender.clone();
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", info.sender))
+}
+
+// Only admin can renounce. The contract becomes its own
+// owner, which no one can act as, so admin messages are
+// disabled for good
+pub fn execute_renounce_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    _msg: RenounceOwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Nobody could unpause the contract afterwards
+    if state.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    PENDING_OWNER.remove(deps.storage);
+    state.owner = env.contract.address;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attribute("action", "renounce_ownership"))
+}
+
+// Only artist can propose a new artist, replacing any
+// pending proposal. The role only moves once accepted
+pub fn execute_transfer_artist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: TransferRoleMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+
+    if state.artist != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pending_artist = validate_address(deps.api, &msg.address)?;
+    PENDING_ARTIST.save(deps.storage, &pending_artist)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer_artist")
+        .add_attribute("pending_artist", pending_artist))
+}
+
+pub fn execute_accept_artist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    _msg: AcceptRoleMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    if PENDING_ARTIST.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PENDING_ARTIST.remove(deps.storage);
+    state.artist = info.sender.clone();
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_artist")
+        .add_attribute("artist", info.sender))
+}
+
+// Only admin can rotate the merkle root of the public
+// whitelist. Clearing it leaves only stored members
+pub fn execute_public_whitelist_merkle_root(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: MerkleRootMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    // Only admin can update the root
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_merkle_root(&msg.merkle_root)?;
+    state.public_whitelist_merkle_root = msg.merkle_root;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "public_whitelist_merkle_root")
+        .add_attribute("merkle_root", state.public_whitelist_merkle_root.unwrap_or_default()))
+}
+
+// Only admin can rotate the merkle root of the private
+// whitelist. Clearing it leaves only stored members
+pub fn execute_private_whitelist_merkle_root(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: MerkleRootMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+
+    // Only admin can update the root
+    if state.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_merkle_root(&msg.merkle_root)?;
+    state.private_whitelist_merkle_root = msg.merkle_root;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "private_whitelist_merkle_root")
+        .add_attribute("merkle_root", state.private_whitelist_merkle_root.unwrap_or_default()))
+}
+
+// Merkle roots are hex-encoded sha256 hashes
+pub fn validate_merkle_root(merkle_root: &Option<String>) -> Result<(), ContractError> {
+    if let Some(merkle_root) = merkle_root {
+        if !matches!(hex::decode(merkle_root), Ok(root) if root.len() == 32) {
+            return Err(ContractError::InvalidMerkleRoot {});
+        }
+    }
+    Ok(())
+}
+
+// Helper fn to verify whitelist proofs. Leaves are the sha256
+// of the address, or of "address:allowance" for leaves with
+// their own allowance. Pairs are hashed in sorted order
+pub fn verify_merkle_proof(
+    merkle_root: &str,
+    proof: &[String],
+    owner: &Addr,
+    allowance: Option<u64>,
+) -> Result<(), ContractError> {
+    let leaf = match allowance {
+        Some(allowance) => format!("{}:{}", owner, allowance),
+        None => owner.to_string(),
+    };
+    let mut hash: [u8; 32] = Sha256::digest(leaf.as_bytes()).into();
+
+    for sibling in proof.iter() {
+        let sibling: [u8; 32] = hex::decode(sibling)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(ContractError::InvalidMerkleProof {})?;
+        let (first, second) = if hash <= sibling {
+            (hash, sibling)
+        } else {
+            (sibling, hash)
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        hash = hasher.finalize().into();
+    }
+
+    let merkle_root = hex::decode(merkle_root).map_err(|_| ContractError::InvalidMerkleRoot {})?;
+    if merkle_root != hash {
+        return Err(ContractError::NotWhitelisted {});
+    }
+    Ok(())
+}
+
+// Tier names must be unique, as members refer to tiers by name
+pub fn validate_whitelist_tiers(tiers: &[WhitelistTier]) -> Result<(), ContractError> {
+    for (i, tier) in tiers.iter().enumerate() {
+        if tiers[..i].iter().any(|other| other.name == tier.name) {
+            return Err(ContractError::DuplicateTier { tier: tier.name.clone() });
+        }
+    }
+    Ok(())
+}
+
+// Payees with valid addresses. Their shares are checked on
+// the resulting config
+pub fn validate_payees(api: &dyn Api, payees: Vec<PayeeMsg>) -> Result<Vec<Payee>, ContractError> {
+    payees
+        .into_iter()
+        .map(|payee| {
+            Ok(Payee {
+                address: validate_address(api, &payee.address)?,
+                share_bps: payee.share_bps,
+            })
+        })
+        .collect()
+}
+
+// Platform fee must be valid, with a valid address
+pub fn validate_platform_fee(api: &dyn Api, platform_fee: PlatformFeeMsg) -> Result<PlatformFee, ContractError> {
+    let platform_fee = PlatformFee {
+        address: validate_address(api, &platform_fee.address)?,
+        amount: platform_fee.amount,
+    };
+    if !platform_fee.is_valid() {
+        return Err(ContractError::InvalidPlatformFee {});
+    }
+    Ok(platform_fee)
+}
+
+// Flat fees are in the native denom, so can't be taken from
+// cw20 payments
+pub fn validate_fee_asset(
+    platform_fee: &Option<PlatformFee>,
+    cw20_price: &Option<Cw20Price>,
+) -> Result<(), ContractError> {
+    if let Some(platform_fee) = platform_fee {
+        if matches!(platform_fee.amount, FeeAmount::Flat(_)) && cw20_price.is_some() {
+            return Err(ContractError::InvalidPlatformFee {});
+        }
+    }
+    Ok(())
+}
+
+// Escrow must be reachable: a minimum the public supply
+// covers, and a deadline still ahead when the terms are set.
+// Terms left as `previous` keep their deadline
+pub fn validate_escrow(state: &State, previous: &Option<Escrow>, env: &Env) -> Result<(), ContractError> {
+    if let Some(escrow) = &state.escrow {
+        if escrow.min_tokens > state.public_supply() {
+            return Err(ContractError::InvalidEscrow {});
+        }
+        if state.escrow != *previous && escrow.deadline <= env.block.time {
+            return Err(ContractError::InvalidEscrow {});
+        }
+    }
+    Ok(())
+}
+
+// Helper fn to validate user supplied addresses, naming
+// the bad entry on failure
+pub fn validate_address(api: &dyn Api, address: &str) -> Result<Addr, ContractError> {
+    api.addr_validate(address).map_err(|_| ContractError::InvalidAddress {
+        address: address.to_string(),
+    })
+}
+
+pub fn validate_cw20_price(api: &dyn Api, price: Cw20PriceMsg) -> Result<Cw20Price, ContractError> {
+    Ok(Cw20Price {
+        address: validate_address(api, &price.address)?,
+        amount: price.amount,
+    })
+}
+
+// Helper fn to enforce minting price. Funds in any other
+// denom are rejected, and the amount sent above the price
+// is returned so it can be refunded to the sender
 pub fn check_sent_required_payment(
     sent: &[Coin],
     required: Option<Coin>,
-) -> Result<(), ContractError> {
-    if let Some(required_coin) = required {
-        let required_amount = required_coin.amount.u128();
-        if required_amount > 0 {
-            let sent_sufficient_funds = sent.iter().any(|coin| {
-                // check if a given sent coin matches denom
-                // and has sufficient amount
-                coin.denom == required_coin.denom && coin.amount.u128() >= required_amount
-            });
-
-            if sent_sufficient_funds {
-                return Ok(());
-            } else {
-                return Err(ContractError::Unauthorized {});
+) -> Result<Uint128, ContractError> {
+    let mut sent_amount = Uint128::zero();
+    for coin in sent.iter() {
+        match &required {
+            Some(required_coin) if coin.denom == required_coin.denom => {
+                sent_amount += coin.amount;
+            }
+            _ => {
+                return Err(ContractError::UnexpectedFunds {
+                    denom: coin.denom.clone(),
+                });
             }
         }
     }
-    Ok(())
+
+    let required_amount = required.map(|coin| coin.amount).unwrap_or_default();
+    if sent_amount < required_amount {
+        return Err(ContractError::InsufficientFunds {
+            required: required_amount,
+            sent: sent_amount,
+        });
+    }
+    Ok(sent_amount - required_amount)
 }
@@ -0,0 +1,225 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+
+use cw_multi_test::{AppResponse, Executor};
+
+use crate::integration_tests::util::{
+    cw721_address, create_whitelist_minter, mint_native, mock_app, query, DENOM,
+};
+use crate::msg::{
+    ConfigUpdateMsg, Cw20PriceMsg, EnablePublicMintMsg, ExecuteMsg, InitMsg, MintMsg, QueryMsg,
+};
+use crate::state::QueriedState;
+use crate::ContractError;
+
+fn attribute<'a>(res: &'a AppResponse, key: &str) -> Option<&'a str> {
+    res.events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.as_str())
+}
+
+// UpdateConfig only touches the fields it's given, and
+// refuses changes unsafe for the sale in progress
+#[test]
+fn test_update_config() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    // Only wlm_admin can update the config
+    let set_prefix = ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+        supply: Some(supply),
+        name_prefix: Some("Piece #".to_string()),
+        ..Default::default()
+    });
+    let err = app
+        .execute_contract(wlm_artist.clone(), wlm.clone(), &set_prefix, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    // Only changed fields are reported
+    let res = app
+        .execute_contract(wlm_admin.clone(), wlm.clone(), &set_prefix, &[])
+        .unwrap();
+    assert_eq!(attribute(&res, "name_prefix"), Some("Piece #"));
+    assert_eq!(attribute(&res, "supply"), None);
+
+    // Other fields are left as they were
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.name_prefix, "Piece #");
+    assert_eq!(config.cw721, nft);
+    assert_eq!(config.public_price, price);
+
+    // Optional settings are cleared with null, and left as
+    // they were when missing
+    let signer = Binary::from([2u8; 33].as_slice());
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+            voucher_signer: Some(Some(signer.clone())),
+            ..Default::default()
+        }),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &set_prefix, &[])
+        .unwrap();
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.voucher_signer, Some(signer));
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+            voucher_signer: Some(None),
+            ..Default::default()
+        }),
+        &[],
+    )
+    .unwrap();
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.voucher_signer, None);
+
+    // The cw20 must be a valid address
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+                cw20_price: Some(Some(Cw20PriceMsg {
+                    address: "Not An Address".to_string(),
+                    amount: price,
+                })),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAddress { address } if address == "Not An Address"
+    ));
+
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Initialize(InitMsg {}),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    // Prices are locked while minting, unless forced
+    let new_price = Uint128::from(20000_u128);
+    let mut set_price = ConfigUpdateMsg {
+        price: Some(new_price),
+        ..Default::default()
+    };
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(set_price.clone()),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PriceLocked {}
+    ));
+    set_price.force = Some(true);
+    let res = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(set_price),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(attribute(&res, "price"), Some("20000"));
+
+    // public_user mints 2 nfts at the new price
+    app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg {
+            quantity: Some(2),
+            ..Default::default()
+        }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: new_price * Uint128::from(2_u64),
+        }],
+    )
+    .unwrap();
+
+    // Supply can't drop below the minted count
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+                supply: Some(1),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SupplyBelowMinted { minted: 2 }
+    ));
+
+    // Nor can the collection move once minting has begun
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+                cw721: Some(wlm_artist.to_string()),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ConfigLocked { field } if field == "cw721"
+    ));
+}
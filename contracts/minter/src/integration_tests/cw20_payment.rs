@@ -0,0 +1,256 @@
+#![cfg(test)]
+use cosmwasm_std::{to_json_binary, Addr, Uint128};
+
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_multi_test::Executor;
+
+use crate::integration_tests::util::{
+    contract_whitelist_minter, create_cw20, create_whitelist_minter, cw721_address,
+    init_whitelist_minter, instantiate_whitelist_minter, mock_app, platform_fee_msg, query,
+    sale_config, whitelist_minter_msg,
+};
+use crate::msg::{
+    Cw20PriceMsg, EnablePublicMintMsg, ExecuteMsg, MintMsg, PlatformFeeMsg, QueryMsg, ReceiveMsg,
+    WithdrawMsg,
+};
+use crate::state::{Cw20Price, FeeAmount, PlatformFee, QueriedState, State};
+use crate::ContractError;
+
+fn cw20_balance(app: &mut cw_multi_test::App, cw20: &Addr, address: &Addr) -> Uint128 {
+    let res: BalanceResponse = query(
+        app,
+        cw20.clone(),
+        Cw20QueryMsg::Balance {
+            address: address.to_string(),
+        },
+    )
+    .unwrap();
+    res.balance
+}
+
+// Mints can be paid with the configured cw20 token
+// through the Receive hook, and the artist can
+// withdraw the cw20 revenue
+#[test]
+fn test_cw20_mint() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // public_user pays with cw20 tokens
+    let public_user = Addr::unchecked("public_customer");
+
+    let balances = vec![Cw20Coin {
+        address: public_user.to_string(),
+        amount: Uint128::from(1000_u128),
+    }];
+    let cw20 = create_cw20(&mut app, &wlm_admin, balances.clone());
+    let other_cw20 = create_cw20(&mut app, &wlm_admin, balances);
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let cw20_price = Uint128::from(100_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        cw20_price: Some(Cw20Price {
+            address: cw20.clone(),
+            amount: cw20_price,
+        }),
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    let mint_two = to_json_binary(&ReceiveMsg::Mint(MintMsg { quantity: Some(2), ..Default::default() })).unwrap();
+
+    // Tokens of any other cw20 are rejected
+    let res = app.execute_contract(
+        public_user.clone(),
+        other_cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: wlm.to_string(),
+            amount: Uint128::from(200_u128),
+            msg: mint_two.clone(),
+        },
+        &[],
+    );
+    assert!(res.is_err());
+
+    // Paying less than the cw20 price fails
+    let res = app.execute_contract(
+        public_user.clone(),
+        cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: wlm.to_string(),
+            amount: Uint128::from(199_u128),
+            msg: mint_two.clone(),
+        },
+        &[],
+    );
+    assert!(res.is_err());
+
+    // Overpayment is transferred back
+    let res = app.execute_contract(
+        public_user.clone(),
+        cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: wlm.to_string(),
+            amount: Uint128::from(250_u128),
+            msg: mint_two,
+        },
+        &[],
+    );
+    assert!(res.is_ok());
+    assert_eq!(cw20_balance(&mut app, &cw20, &public_user), Uint128::from(800_u128));
+    assert_eq!(cw20_balance(&mut app, &cw20, &wlm), Uint128::from(200_u128));
+
+    // Only the artist can withdraw cw20 revenue
+    let withdraw = ExecuteMsg::Withdraw(WithdrawMsg {
+        amount: Uint128::from(200_u128),
+        cw20: Some(cw20.clone()),
+    });
+    let res = app.execute_contract(public_user.clone(), wlm.clone(), &withdraw, &[]);
+    assert!(res.is_err());
+    app.execute_contract(wlm_artist.clone(), wlm.clone(), &withdraw, &[])
+        .unwrap();
+    assert_eq!(cw20_balance(&mut app, &cw20, &wlm_artist), Uint128::from(200_u128));
+}
+
+// The cw20 set at instantiation must be a valid address
+#[test]
+fn test_cw20_price_address() {
+    let mut app = mock_app();
+
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    let cw20 = create_cw20(&mut app, &wlm_admin, vec![]);
+
+    let price = Uint128::from(10000_u128);
+    let mut msg = whitelist_minter_msg(&mut app, 3333, 5, vec![], wlm_artist, price);
+    msg.cw20_price = Some(Cw20PriceMsg {
+        address: "Not An Address".to_string(),
+        amount: Uint128::from(100_u128),
+    });
+    let wlm_id = app.store_code(contract_whitelist_minter());
+    let err = app
+        .instantiate_contract(wlm_id, wlm_admin.clone(), &msg, &[], "whitelist-minter", None)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAddress { address } if address == "Not An Address"
+    ));
+
+    msg.cw20_price = Some(Cw20PriceMsg {
+        address: cw20.to_string(),
+        amount: Uint128::from(100_u128),
+    });
+    let wlm = instantiate_whitelist_minter(&mut app, wlm_admin, &msg);
+    let config: QueriedState = query(&mut app, wlm, QueryMsg::Config {}).unwrap();
+    assert_eq!(
+        config.cw20_price,
+        Some(Cw20Price {
+            address: cw20,
+            amount: Uint128::from(100_u128),
+        })
+    );
+}
+
+// Platform fees in basis points are also taken from cw20
+// payments, while flat fees are only for native payments
+#[test]
+fn test_cw20_platform_fee() {
+    let mut app = mock_app();
+
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    let platform = Addr::unchecked("platform");
+    let public_user = Addr::unchecked("public_customer");
+
+    let balances = vec![Cw20Coin {
+        address: public_user.to_string(),
+        amount: Uint128::from(1000_u128),
+    }];
+    let cw20 = create_cw20(&mut app, &wlm_admin, balances);
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let cw20_price = Cw20Price {
+        address: cw20.clone(),
+        amount: Uint128::from(100_u128),
+    };
+    let mut msg = whitelist_minter_msg(&mut app, supply, 5, vec![], wlm_artist.clone(), price);
+    msg.cw20_price = Some(Cw20PriceMsg {
+        address: cw20.to_string(),
+        amount: cw20_price.amount,
+    });
+
+    // Flat fees can't be charged in the cw20
+    msg.platform_fee = Some(PlatformFeeMsg {
+        address: platform.to_string(),
+        amount: FeeAmount::Flat(Uint128::from(10_u128)),
+    });
+    let wlm_id = app.store_code(contract_whitelist_minter());
+    let err = app
+        .instantiate_contract(wlm_id, wlm_admin.clone(), &msg, &[], "whitelist-minter", None)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidPlatformFee {}
+    ));
+
+    // 10% of every payment goes to the platform
+    let platform_fee = PlatformFee {
+        address: platform.clone(),
+        amount: FeeAmount::Bps(1000),
+    };
+    msg.platform_fee = Some(platform_fee_msg(&platform_fee));
+    let wlm = instantiate_whitelist_minter(&mut app, wlm_admin.clone(), &msg);
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        cw20_price: Some(cw20_price),
+        platform_fee: Some(platform_fee),
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    let mint_two = to_json_binary(&ReceiveMsg::Mint(MintMsg { quantity: Some(2), ..Default::default() })).unwrap();
+    app.execute_contract(
+        public_user.clone(),
+        cw20.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: wlm.to_string(),
+            amount: Uint128::from(200_u128),
+            msg: mint_two,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(cw20_balance(&mut app, &cw20, &platform), Uint128::from(20_u128));
+    assert_eq!(cw20_balance(&mut app, &cw20, &wlm), Uint128::from(180_u128));
+}
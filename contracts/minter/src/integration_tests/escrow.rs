@@ -0,0 +1,327 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Coin, Uint128};
+
+use cw_multi_test::{App, Executor};
+
+use crate::token::{Extension, ExecuteMsg as Cw721ExecuteMsg, QueryMsg as Cw721QueryMsg};
+use crate::token::cw721::NumTokensResponse;
+
+use crate::integration_tests::util::{
+    bank_query, contract_whitelist_minter, cw721_address, init_whitelist_minter,
+    instantiate_whitelist_minter, mint_native, mock_app, platform_fee_msg, query, sale_config,
+    whitelist_minter_msg, DENOM,
+};
+use crate::msg::{
+    ClaimRefundMsg, ConfigUpdateMsg, EnablePublicMintMsg, ExecuteMsg, MintMsg, QueryMsg,
+    WithdrawMsg,
+};
+use crate::query::SaleStats;
+use crate::state::{Escrow, FeeAmount, PlatformFee, State};
+use crate::ContractError;
+
+// Deploys a public sale in escrow until 5 tokens are sold,
+// within 1000 seconds, taking a 10% platform fee. Returns
+// the (wlm, nft) contracts
+fn escrow_minter(
+    app: &mut App,
+    wlm_admin: &Addr,
+    wlm_artist: &Addr,
+    platform: &Addr,
+    price: Uint128,
+) -> (Addr, Addr) {
+    let supply: u64 = 3333;
+    let platform_fee = PlatformFee {
+        address: platform.clone(),
+        amount: FeeAmount::Bps(1000),
+    };
+    let mut msg = whitelist_minter_msg(app, supply, 5, vec![], wlm_artist.clone(), price);
+    msg.platform_fee = Some(platform_fee_msg(&platform_fee));
+    let wlm = instantiate_whitelist_minter(app, wlm_admin.clone(), &msg);
+    let nft = cw721_address(app, &wlm);
+
+    let config_update = State {
+        escrow: Some(Escrow {
+            min_tokens: 5,
+            deadline: app.block_info().time.plus_seconds(1000),
+        }),
+        platform_fee: Some(platform_fee),
+        ..sale_config(wlm_admin, &nft, wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    (wlm, nft)
+}
+
+fn mint(app: &mut App, wlm: &Addr, minter: &Addr, quantity: u64, price: Uint128) -> Result<(), ContractError> {
+    app.execute_contract(
+        minter.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg {
+            quantity: Some(quantity),
+            ..Default::default()
+        }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(quantity),
+        }],
+    )
+    .map(|_| ())
+    .map_err(|err| err.downcast().unwrap())
+}
+
+fn withdraw(app: &mut App, wlm: &Addr, wlm_artist: &Addr, amount: Uint128) -> Result<(), ContractError> {
+    app.execute_contract(
+        wlm_artist.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Withdraw(WithdrawMsg { amount, cw20: None }),
+        &[],
+    )
+    .map(|_| ())
+    .map_err(|err| err.downcast().unwrap())
+}
+
+// Minters get their payment back, and their tokens burned,
+// when the minimum isn't sold before the deadline
+#[test]
+fn test_escrow_refund() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721 and receives the revenue
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+    let other_user = Addr::unchecked("other_customer");
+    // platform is owed a fee on every mint
+    let platform = Addr::unchecked("platform");
+
+    let funds = Uint128::from(100000000000000000000_u128); // 100 ARCH as aarch
+    mint_native(&mut app, public_user.to_string(), funds, DENOM);
+    mint_native(&mut app, other_user.to_string(), funds, DENOM);
+
+    let price = Uint128::from(10000_u128);
+    let (wlm, nft) = escrow_minter(&mut app, &wlm_admin, &wlm_artist, &platform, price);
+
+    // public_user mints 3 nfts, short of the minimum
+    mint(&mut app, &wlm, &public_user, 3, price).unwrap();
+
+    // Revenue is locked while the sale is pending
+    let err = withdraw(&mut app, &wlm, &wlm_artist, price).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::EscrowLocked {}
+    ));
+
+    // Nor can it be refunded yet
+    let claim = ExecuteMsg::ClaimRefund(ClaimRefundMsg {
+        token_ids: vec!["1".to_string(), "2".to_string()],
+    });
+    let err = app
+        .execute_contract(public_user.clone(), wlm.clone(), &claim, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RefundUnavailable {}
+    ));
+
+    // The deadline passes, failing the sale
+    app.update_block(|block| block.time = block.time.plus_seconds(1000));
+    let err = mint(&mut app, &wlm, &other_user, 2, price).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::MintExpired {}
+    ));
+    let err = withdraw(&mut app, &wlm, &wlm_artist, price).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::EscrowLocked {}
+    ));
+
+    // Only the owner of the tokens can claim them
+    let err = app
+        .execute_contract(other_user.clone(), wlm.clone(), &claim, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    // Refunded nfts are burned, which wlm can't do until
+    // public_user approves it
+    let err = app
+        .execute_contract(public_user.clone(), wlm.clone(), &claim, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BurnNotApproved {}
+    ));
+
+    // public_user lets wlm burn their nfts and claims 2 of them
+    let approve_msg: Cw721ExecuteMsg = Cw721ExecuteMsg::ApproveAll {
+        operator: wlm.to_string(),
+        expires: None,
+    };
+    app.execute_contract(public_user.clone(), nft.clone(), &approve_msg, &[])
+        .unwrap();
+    app.execute_contract(public_user.clone(), wlm.clone(), &claim, &[])
+        .unwrap();
+
+    // Refunds include the platform fee, which was held with
+    // the payment
+    let paid = price * Uint128::from(3_u64);
+    let refunded = price * Uint128::from(2_u64);
+    assert_eq!(bank_query(&app, &public_user, DENOM).amount, funds - paid + refunded);
+    assert_eq!(bank_query(&app, &wlm, DENOM).amount, price);
+    assert_eq!(bank_query(&app, &platform, DENOM).amount, Uint128::zero());
+
+    // Refunded payments are taken out of the raised total
+    let stats: SaleStats = query(&mut app, wlm.clone(), QueryMsg::SaleStats {}).unwrap();
+    assert_eq!(stats.raised[0].amount, price.multiply_ratio(9_u128, 10_u128));
+    let num_tokens: NumTokensResponse =
+        query(&mut app, nft.clone(), Cw721QueryMsg::<Extension>::NumTokens {}).unwrap();
+    assert_eq!(num_tokens.count, 1);
+
+    // Burned tokens can't be refunded twice
+    let res = app.execute_contract(public_user.clone(), wlm.clone(), &claim, &[]);
+    assert!(res.is_err());
+}
+
+// Revenue is released to the artist once the minimum is
+// sold, even past the deadline
+#[test]
+fn test_escrow_release() {
+    let mut app = mock_app();
+
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    let public_user = Addr::unchecked("public_customer");
+    let platform = Addr::unchecked("platform");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let price = Uint128::from(10000_u128);
+    let (wlm, _nft) = escrow_minter(&mut app, &wlm_admin, &wlm_artist, &platform, price);
+
+    mint(&mut app, &wlm, &public_user, 5, price).unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(1000));
+    let claim = ExecuteMsg::ClaimRefund(ClaimRefundMsg {
+        token_ids: vec!["1".to_string()],
+    });
+    let err = app
+        .execute_contract(public_user.clone(), wlm.clone(), &claim, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RefundUnavailable {}
+    ));
+
+    // Minting goes on, and wlm_artist withdraws the revenue,
+    // but not the platform fees held with it
+    mint(&mut app, &wlm, &public_user, 1, price).unwrap();
+    let paid = price * Uint128::from(6_u64);
+    let fee = paid.multiply_ratio(1_u128, 10_u128);
+    let err = withdraw(&mut app, &wlm, &wlm_artist, paid).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::InsufficientBalance { .. }
+    ));
+    withdraw(&mut app, &wlm, &wlm_artist, paid - fee).unwrap();
+    assert_eq!(bank_query(&app, &wlm_artist, DENOM).amount, paid - fee);
+
+    // Held fees are sent to the platform on release
+    assert_eq!(bank_query(&app, &platform, DENOM).amount, fee);
+    assert_eq!(bank_query(&app, &wlm, DENOM).amount, Uint128::zero());
+}
+
+// A sale can only be put in escrow on terms it can meet:
+// a minimum within the public supply, by a future deadline
+#[test]
+fn test_escrow_terms() {
+    let mut app = mock_app();
+
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    let wlm_artist = Addr::unchecked("cw721_artist");
+
+    let supply: u64 = 10;
+    let price = Uint128::from(10000_u128);
+    let now = app.block_info().time;
+    let mut msg = whitelist_minter_msg(&mut app, supply, 5, vec![], wlm_artist.clone(), price);
+    msg.total_reserved = 2;
+    msg.escrow = Some(Escrow {
+        min_tokens: 9,
+        deadline: now.plus_seconds(1000),
+    });
+    let wlm_id = app.store_code(contract_whitelist_minter());
+    let err = app
+        .instantiate_contract(wlm_id, wlm_admin.clone(), &msg, &[], "whitelist-minter", None)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidEscrow {}
+    ));
+
+    msg.escrow = None;
+    let wlm = instantiate_whitelist_minter(&mut app, wlm_admin.clone(), &msg);
+    let set_escrow = |escrow: Escrow| {
+        ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+            escrow: Some(Some(escrow)),
+            ..Default::default()
+        })
+    };
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &set_escrow(Escrow {
+                min_tokens: 8,
+                deadline: now,
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidEscrow {}
+    ));
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &set_escrow(Escrow {
+            min_tokens: 8,
+            deadline: now.plus_seconds(1000),
+        }),
+        &[],
+    )
+    .unwrap();
+
+    // Nor can the reserved allocations grow past the minimum
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+                total_reserved: Some(3),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidEscrow {}
+    ));
+}
@@ -1,13 +1,16 @@
 #![cfg(test)]
 use cosmwasm_std::{Addr, Uint128};
 
+use cw_multi_test::Executor;
+
 use crate::token::{Extension, QueryMsg as Cw721QueryMsg};
 use crate::token::cw721::{NumTokensResponse, TokensResponse};
 
 use crate::integration_tests::util::{
-    create_cw721, create_whitelist_minter, init_whitelist_minter, mock_app, query, NAME_PREFIX,
+    contract_whitelist_minter, create_whitelist_minter, cw721_address, init_whitelist_minter,
+    mock_app, query, sale_config, whitelist_minter_msg,
 };
-use crate::state::State;
+use crate::ContractError;
 
 #[test]
 fn test_initialize() {
@@ -18,8 +21,8 @@ fn test_initialize() {
     // wlm_artist owns the cw721
     let wlm_artist = Addr::unchecked("cw721_artist");
 
-    // wlm_admin creates the wlm contract; cw721 address
-    // is not yet properly initialized in wlm State
+    // wlm_admin creates the wlm contract, which
+    // instantiates its cw721 as the minter
     let supply: u64 = 3333;
     let whitelist_allowance: u64 = 5;
     let whitelist: Vec<Addr> = vec![wlm_admin.clone(), wlm_artist.clone()];
@@ -33,22 +36,40 @@ fn test_initialize() {
         Uint128::from(10000000000000000000_u128), // minting price of 10 ARCH (as aarch)
     );
 
-    // create the NFT contract with wlm as minter
-    let nft = create_cw721(&mut app, &wlm);
+    // the NFT contract instantiated by wlm
+    let nft = cw721_address(&mut app, &wlm);
+    let num_tokens: NumTokensResponse =
+        query(&mut app, nft.clone(), Cw721QueryMsg::<Extension>::NumTokens {}).unwrap();
+    assert_eq!(num_tokens.count, 0);
 
-    // wlm_admin updates the wlm contract with
-    // the correct cw721 address to be used for
-    // minting and revealing
-    let config_update = State {
-        owner: wlm_admin.clone(),
-        cw721: nft.clone(),
-        artist: wlm_artist.clone(),
+    // wlm_admin configures the sale
+    let config_update = sale_config(
+        &wlm_admin,
+        &nft,
+        &wlm_artist,
         supply,
-        phase: Default::default(),
-        private_whitelist_allowance: whitelist_allowance,
-        price: Uint128::from(10000000000000000000_u128), // price
-        name_prefix: NAME_PREFIX.to_string(),
-        public_whitelist_allowance: whitelist_allowance,
-    };
+        whitelist_allowance,
+        Uint128::from(10000000000000000000_u128), // minting price of 10 ARCH (as aarch)
+    );
     let _res = init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
 }
+
+// Whitelist members listed twice are rejected by name
+#[test]
+fn test_initialize_duplicate_members() {
+    let mut app = mock_app();
+
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    let wlm_artist = Addr::unchecked("cw721_artist");
+
+    let whitelist = vec![wlm_artist.clone(), wlm_admin.clone(), wlm_artist.clone()];
+    let msg = whitelist_minter_msg(&mut app, 3333, 5, whitelist, wlm_artist.clone(), Uint128::from(10000_u128));
+    let wlm_id = app.store_code(contract_whitelist_minter());
+    let err = app
+        .instantiate_contract(wlm_id, wlm_admin, &msg, &[], "whitelist-minter", None)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DuplicateMember { address } if address == wlm_artist.as_str()
+    ));
+}
@@ -0,0 +1,182 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Coin, Uint128};
+
+use cw_multi_test::Executor;
+use sha2::{Digest, Sha256};
+
+use crate::integration_tests::util::{
+    config_update_msg, create_whitelist_minter, cw721_address, init_whitelist_minter, mint_native,
+    mock_app, sale_config, DENOM,
+};
+use crate::msg::{ExecuteMsg, MerkleRootMsg, MintMsg};
+use crate::state::State;
+use crate::ContractError;
+
+fn leaf_hash(leaf: &str) -> Vec<u8> {
+    Sha256::digest(leaf.as_bytes()).to_vec()
+}
+
+fn pair_hash(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().to_vec()
+}
+
+// Whitelisted users mint during the private whitelist by
+// proving their leaf against the merkle root, without the
+// admin storing any address
+#[test]
+fn test_merkle_whitelist() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // alice is in the tree with the default allowance
+    let alice = Addr::unchecked("alice");
+    // bob is in the tree with an allowance of 3
+    let bob = Addr::unchecked("bob");
+    // mallory is not in the tree
+    let mallory = Addr::unchecked("mallory");
+
+    for user in [&alice, &bob, &mallory] {
+        mint_native(
+            &mut app,
+            user.to_string(),
+            Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+            DENOM,
+        );
+    }
+
+    // Tree of four leaves, pairs hashed in sorted order
+    let leaves: Vec<Vec<u8>> = ["alice", "bob:3", "carol", "dave"]
+        .iter()
+        .map(|leaf| leaf_hash(leaf))
+        .collect();
+    let left = pair_hash(&leaves[0], &leaves[1]);
+    let right = pair_hash(&leaves[2], &leaves[3]);
+    let merkle_root = hex::encode(pair_hash(&left, &right));
+    let alice_proof = vec![hex::encode(&leaves[1]), hex::encode(&right)];
+    let bob_proof = vec![hex::encode(&leaves[0]), hex::encode(&right)];
+
+    let supply: u64 = 3333;
+    let whitelist_allowance: u64 = 5;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        whitelist_allowance,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        private_whitelist_merkle_root: Some(merkle_root),
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, whitelist_allowance, price)
+    };
+
+    // Roots must be sha256 hashes
+    let mut invalid_root = config_update.clone();
+    invalid_root.private_whitelist_merkle_root = Some("not_a_root".to_string());
+    let res = app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::UpdateConfig(config_update_msg(invalid_root)),
+        &[],
+    );
+    assert!(res.is_err());
+
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    let funds = |quantity: u64| {
+        [Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(quantity),
+        }]
+    };
+
+    // alice mints her default allowance with a proof
+    let res = app.execute_contract(
+        alice.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg {
+            quantity: Some(whitelist_allowance),
+            proof: Some(alice_proof.clone()),
+            ..Default::default()
+        }),
+        &funds(whitelist_allowance),
+    );
+    assert!(res.is_ok());
+
+    // Counter still enforces the allowance
+    let err = app
+        .execute_contract(
+            alice.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Mint(MintMsg {
+                proof: Some(alice_proof.clone()),
+                ..Default::default()
+            }),
+            &funds(1),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::WhitelistAllowance { .. }
+    ));
+
+    // mallory can't reuse someone else's proof
+    let err = app
+        .execute_contract(
+            mallory.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Mint(MintMsg {
+                proof: Some(alice_proof.clone()),
+                ..Default::default()
+            }),
+            &funds(1),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotWhitelisted {}
+    ));
+
+    // bob can't claim more than the allowance in his leaf
+    let bob_mint = |quantity: u64, allowance: u64| {
+        ExecuteMsg::Mint(MintMsg {
+            quantity: Some(quantity),
+            proof: Some(bob_proof.clone()),
+            allowance: Some(allowance),
+            ..Default::default()
+        })
+    };
+    let res = app.execute_contract(bob.clone(), wlm.clone(), &bob_mint(4, 10), &funds(4));
+    assert!(res.is_err());
+    let res = app.execute_contract(bob.clone(), wlm.clone(), &bob_mint(4, 3), &funds(4));
+    assert!(res.is_err());
+    let res = app.execute_contract(bob.clone(), wlm.clone(), &bob_mint(3, 3), &funds(3));
+    assert!(res.is_ok());
+
+    // Only wlm_admin can rotate the root
+    let clear_root = ExecuteMsg::PrivateWhitelistMerkleRoot(MerkleRootMsg { merkle_root: None });
+    let res = app.execute_contract(alice.clone(), wlm.clone(), &clear_root, &[]);
+    assert!(res.is_err());
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &clear_root, &[])
+        .unwrap();
+
+    // Proofs are ignored once the root is cleared
+    let res = app.execute_contract(
+        bob.clone(),
+        wlm.clone(),
+        &bob_mint(1, 3),
+        &funds(1),
+    );
+    assert!(res.is_err());
+}
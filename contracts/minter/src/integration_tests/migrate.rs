@@ -0,0 +1,130 @@
+#![cfg(test)]
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{Addr, Uint128};
+use cw2::{get_contract_version, set_contract_version};
+use rstest::rstest;
+
+use crate::contract::migrate;
+use crate::migrate::{StateV1, STATE_V1};
+use crate::msg::{ConfigUpdateMsg, MigrateMsg};
+use crate::state::{
+    Phase, WhitelistKind, WhitelistMember, WhitelistStats, MINTED_COUNT, PRIVATE_WHITELIST,
+    PRIVATE_WHITELIST_COUNTER, STATE,
+};
+use crate::ContractError;
+
+static CONTRACT_NAME: &str = "whitelist-minter";
+
+fn v1_state() -> StateV1 {
+    StateV1 {
+        owner: Addr::unchecked("whitelist_minter_owner"),
+        cw721: Addr::unchecked("cw721"),
+        artist: Addr::unchecked("cw721_artist"),
+        supply: 3333,
+        phase: Phase::Public,
+        private_whitelist_allowance: 5,
+        public_whitelist_allowance: 5,
+        price: Uint128::from(10000_u128),
+        name_prefix: "Token #".to_string(),
+    }
+}
+
+// A contract deployed with v1.0.1 is moved to the current
+// layout, counting burned tokens as minted
+#[test]
+fn test_migrate_from_v1() {
+    let mut deps = mock_dependencies();
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0.1").unwrap();
+    STATE_V1.save(deps.as_mut().storage, &v1_state()).unwrap();
+
+    // Two private whitelist members, one of whom minted their
+    // full allowance
+    let storage = deps.as_mut().storage;
+    for (member, minted) in [("alice", 5_u64), ("bob", 2)] {
+        let member = Addr::unchecked(member);
+        let whitelist_member = WhitelistMember { whitelisted: true, ..Default::default() };
+        PRIVATE_WHITELIST.save(storage, &member, &whitelist_member).unwrap();
+        PRIVATE_WHITELIST_COUNTER.save(storage, &member, &minted).unwrap();
+    }
+
+    // 10 tokens were minted, and the last 2 burned since, so
+    // the collection alone would undercount the ids used
+    deps.querier.update_wasm(|_| panic!("unexpected query"));
+
+    let new_price = Uint128::from(20000_u128);
+    let msg = MigrateMsg {
+        denom: Some("aconst".to_string()),
+        minted_count: Some(10),
+        config: Some(ConfigUpdateMsg {
+            price: Some(new_price),
+            force: Some(true),
+            ..Default::default()
+        }),
+    };
+    migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    assert_eq!(state.owner, Addr::unchecked("whitelist_minter_owner"));
+    assert_eq!(state.phase, Phase::Public);
+    assert_eq!(state.denom, "aconst");
+    assert_eq!(state.price, new_price);
+    assert_eq!(state.private_whitelist_price, Uint128::from(10000_u128));
+    assert_eq!(MINTED_COUNT.load(deps.as_ref().storage).unwrap(), 10);
+
+    // Whitelist stats are counted from the stored lists
+    let stats = WhitelistKind::Private.stats().load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        stats,
+        WhitelistStats {
+            member_count: 2,
+            total_minted: 7,
+            exhausted_count: 1,
+        }
+    );
+
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+}
+
+// The minted count of a v1 contract can't be inferred, so
+// migrating without it fails, and the denom defaults to aarch
+#[test]
+fn test_migrate_from_v1_minted_count() {
+    let mut deps = mock_dependencies();
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0.1").unwrap();
+    STATE_V1.save(deps.as_mut().storage, &v1_state()).unwrap();
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert!(matches!(err, ContractError::MissingMintedCount {}));
+
+    let msg = MigrateMsg {
+        minted_count: Some(12),
+        ..Default::default()
+    };
+    migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(MINTED_COUNT.load(deps.as_ref().storage).unwrap(), 12);
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    assert_eq!(state.denom, "aarch");
+}
+
+// Versions are compared as semver, so only upgrades go through
+#[rstest]
+#[case(env!("CARGO_PKG_VERSION"))]
+#[case("1.10.0")]
+#[case("10.0.0")]
+fn test_migrate_rejects_downgrades(#[case] stored: &str) {
+    let mut deps = mock_dependencies();
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, stored).unwrap();
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert!(matches!(err, ContractError::CannotMigrate { .. }));
+}
+
+#[test]
+fn test_migrate_invalid_version() {
+    let mut deps = mock_dependencies();
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0").unwrap();
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert!(matches!(err, ContractError::SemVer(_)));
+}
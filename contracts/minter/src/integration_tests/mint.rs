@@ -4,16 +4,21 @@ use cosmwasm_std::{Addr, Coin, Uint128};
 use cw_multi_test::Executor;
 
 use crate::token::{Extension, QueryMsg as Cw721QueryMsg};
-use crate::token::cw721::OwnerOfResponse;
+use crate::token::cw721::{NumTokensResponse, OwnerOfResponse};
 use rstest::rstest;
 
-use crate::contract::DENOM;
 use crate::integration_tests::util::{
-    create_cw721, create_whitelist_minter, init_whitelist_minter, mint_native, mock_app, query,
-    NAME_PREFIX,
+    bank_query, create_whitelist_minter, cw721_address, init_whitelist_minter,
+    instantiate_whitelist_minter, mint_native, mock_app, platform_fee_msg, query, sale_config,
+    whitelist_minter_msg, DENOM,
 };
-use crate::msg::{EnablePublicMintMsg, EnableWhitelistMintMsg, ExecuteMsg, MintMsg};
-use crate::state::State;
+use crate::msg::{
+    ConfigUpdateMsg, EnablePublicMintMsg, EnableWhitelistMintMsg, ExecuteMsg, MintMsg,
+    PlatformAddressMsg, QueryMsg, WhitelistApproveMsg, WhitelistEntry, WithdrawMsg,
+};
+use crate::query::{AssetAmount, MintEligibility, MintedCount, PhaseRaised, SaleStats};
+use crate::state::{FeeAmount, PlatformFee, QueriedState, State, WhitelistTier};
+use crate::ContractError;
 
 // Only whitelisted users can mint during the whitelist
 // period (e.g. reveal == false). After the whitelist
@@ -40,6 +45,7 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
         &mut app,
         wlm_user.to_string(),
         Uint128::from(10000000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
     );
 
     // mint natve ARCH tokens to public_user
@@ -47,10 +53,11 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
         &mut app,
         public_user.to_string(),
         Uint128::from(10000000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
     );
 
-    // wlm_admin creates the wlm contract; cw721 address
-    // is not yet properly initialized in wlm State
+    // wlm_admin creates the wlm contract, which
+    // instantiates its cw721 as the minter
     let supply: u64 = 3333;
     let whitelist_allowance: u64 = whitelist_limit;
     let whitelist: Vec<Addr> = vec![wlm_user.clone()];
@@ -64,23 +71,18 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
         Uint128::from(10000000000000000000_u128), // minting price of 10 ARCH (as aarch)
     );
 
-    // create the NFT contract with wlm as minter
-    let nft = create_cw721(&mut app, &wlm);
+    // the NFT contract instantiated by wlm
+    let nft = cw721_address(&mut app, &wlm);
 
-    // wlm_admin updates the wlm contract with
-    // the correct cw721 address to be used for
-    // minting and revealing
-    let config_update = State {
-        owner: wlm_admin.clone(),
-        cw721: nft.clone(),
-        artist: wlm_artist.clone(),
+    // wlm_admin configures the sale
+    let config_update = sale_config(
+        &wlm_admin,
+        &nft,
+        &wlm_artist,
         supply,
-        phase: Default::default(),
-        private_whitelist_allowance: whitelist_allowance,
-        public_whitelist_allowance: whitelist_allowance,
-        price: Uint128::from(10000000000000000000_u128), // price
-        name_prefix: NAME_PREFIX.to_string(),
-    };
+        whitelist_allowance,
+        Uint128::from(10000000000000000000_u128), // minting price of 10 ARCH (as aarch)
+    );
     let _res = init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
 
     // wlm_user must pay the correct minting price
@@ -88,7 +90,7 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
     let res = app.execute_contract(
         wlm_user.clone(),
         wlm.clone(),
-        &ExecuteMsg::Mint(MintMsg {}),
+        &ExecuteMsg::Mint(MintMsg::default()),
         &[Coin {
             denom: DENOM.to_string(),
             amount: Uint128::from(10000_u128), // not enough funds sent
@@ -101,7 +103,7 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
     let res = app.execute_contract(
         wlm_user.clone(),
         wlm.clone(),
-        &ExecuteMsg::Mint(MintMsg {}),
+        &ExecuteMsg::Mint(MintMsg::default()),
         &[Coin {
             denom: DENOM.to_string(),
             amount: Uint128::from(10000000000000000000_u128), // correct funds
@@ -128,7 +130,7 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
         let res = app.execute_contract(
             wlm_user.clone(),
             wlm.clone(),
-            &ExecuteMsg::Mint(MintMsg {}),
+            &ExecuteMsg::Mint(MintMsg::default()),
             &[Coin {
                 denom: DENOM.to_string(),
                 amount: Uint128::from(10000000000000000000_u128),
@@ -141,7 +143,7 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
     let res = app.execute_contract(
         wlm_user.clone(),
         wlm.clone(),
-        &ExecuteMsg::Mint(MintMsg {}),
+        &ExecuteMsg::Mint(MintMsg::default()),
         &[Coin {
             denom: DENOM.to_string(),
             amount: Uint128::from(10000000000000000000_u128),
@@ -154,7 +156,7 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
     let res = app.execute_contract(
         public_user.clone(),
         wlm.clone(),
-        &ExecuteMsg::Mint(MintMsg {}),
+        &ExecuteMsg::Mint(MintMsg::default()),
         &[Coin {
             denom: DENOM.to_string(),
             amount: Uint128::from(10000000000000000000_u128),
@@ -174,7 +176,7 @@ fn test_whitelist_permissions(#[case] whitelist_limit: u64) {
     let res = app.execute_contract(
         public_user.clone(),
         wlm.clone(),
-        &ExecuteMsg::Mint(MintMsg {}),
+        &ExecuteMsg::Mint(MintMsg::default()),
         &[Coin {
             denom: DENOM.to_string(),
             amount: Uint128::from(10000000000000000000_u128),
@@ -208,8 +210,8 @@ fn test_mint_all_nfts() {
     // wlm_user mints and reveals nfts, and pays the minting price
     let wlm_user = Addr::unchecked("some_customer");
 
-    // wlm_admin creates the wlm contract; cw721 address
-    // is not yet properly initialized in wlm State
+    // wlm_admin creates the wlm contract, which
+    // instantiates its cw721 as the minter
     let supply: u64 = 3333;
     let whitelist_allowance: u64 = 5;
     let whitelist: Vec<Addr> = vec![wlm_admin.clone(), wlm_artist.clone(), wlm_user.clone()];
@@ -223,30 +225,26 @@ fn test_mint_all_nfts() {
         Uint128::from(10000_u128), // minting price really cheap
     );
 
-    // create the NFT contract with wlm as minter
-    let nft = create_cw721(&mut app, &wlm);
+    // the NFT contract instantiated by wlm
+    let nft = cw721_address(&mut app, &wlm);
 
     // mint natve ARCH tokens to wlm_user
     mint_native(
         &mut app,
         wlm_user.to_string(),
         Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
     );
 
-    // wlm_admin updates the wlm contract with
-    // the correct cw721 address to be used for
-    // minting and revealing
-    let config_update = State {
-        owner: wlm_admin.clone(),
-        cw721: nft.clone(),
-        artist: wlm_artist.clone(),
+    // wlm_admin configures the sale
+    let config_update = sale_config(
+        &wlm_admin,
+        &nft,
+        &wlm_artist,
         supply,
-        phase: Default::default(),
-        private_whitelist_allowance: whitelist_allowance,
-        public_whitelist_allowance: whitelist_allowance,
-        price: Uint128::from(10000_u128), // price
-        name_prefix: NAME_PREFIX.to_string(),
-    };
+        whitelist_allowance,
+        Uint128::from(10000_u128),
+    );
     init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
 
     // wlm_admin enables public mint
@@ -270,7 +268,7 @@ fn test_mint_all_nfts() {
         let res = app.execute_contract(
             wlm_user.clone(),
             wlm.clone(),
-            &ExecuteMsg::Mint(MintMsg {}),
+            &ExecuteMsg::Mint(MintMsg::default()),
             &[Coin {
                 denom: DENOM.to_string(),
                 amount: Uint128::from(10000_u128),
@@ -285,7 +283,7 @@ fn test_mint_all_nfts() {
     let res = app.execute_contract(
         wlm_user.clone(),
         wlm.clone(),
-        &ExecuteMsg::Mint(MintMsg {}),
+        &ExecuteMsg::Mint(MintMsg::default()),
         &[Coin {
             denom: DENOM.to_string(),
             amount: Uint128::from(10000_u128),
@@ -293,3 +291,1041 @@ fn test_mint_all_nfts() {
     );
     assert!(res.is_err());
 }
+
+// Each phase charges its own price, and the price
+// in effect is reported by the config query
+#[test]
+fn test_phase_prices() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // wlm_user mints in every phase
+    let wlm_user = Addr::unchecked("wlm_customer");
+
+    mint_native(
+        &mut app,
+        wlm_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let whitelist_allowance: u64 = 5;
+    let whitelist_price = Uint128::from(5000_u128);
+    let public_price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        whitelist_allowance,
+        vec![wlm_user.clone()],
+        wlm_artist.clone(),
+        public_price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        private_whitelist_price: Uint128::zero(), // free private phase
+        public_whitelist_price: whitelist_price,
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, whitelist_allowance, public_price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    // Private whitelist mints are free
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.price, Uint128::zero());
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[],
+    );
+    assert!(res.is_ok());
+
+    // Normal whitelist charges the discounted price
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnableNormalWhitelist(EnableWhitelistMintMsg {}),
+        &[],
+    )
+    .unwrap();
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.price, whitelist_price);
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[],
+    );
+    assert!(res.is_err());
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: whitelist_price,
+        }],
+    );
+    assert!(res.is_ok());
+
+    // Public mint charges the full price
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.price, public_price);
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: whitelist_price,
+        }],
+    );
+    assert!(res.is_err());
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: public_price,
+        }],
+    );
+    assert!(res.is_ok());
+}
+
+// A single transaction can mint several tokens, paying
+// for all of them and counting them all against the
+// whitelist allowance. Batches that don't fit the
+// remaining supply fail without minting anything
+#[test]
+fn test_batch_mint() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // wlm_user mints in batches
+    let wlm_user = Addr::unchecked("wlm_customer");
+
+    mint_native(
+        &mut app,
+        wlm_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 8;
+    let whitelist_allowance: u64 = 5;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        whitelist_allowance,
+        vec![wlm_user.clone()],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = sale_config(
+        &wlm_admin,
+        &nft,
+        &wlm_artist,
+        supply,
+        whitelist_allowance,
+        price,
+    );
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    // Batch must fit in the whitelist allowance
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg { quantity: Some(whitelist_allowance + 1), ..Default::default() }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(whitelist_allowance + 1),
+        }],
+    );
+    assert!(res.is_err());
+
+    // Batch sizes that overflow the count are over the allowance
+    let err = app
+        .execute_contract(
+            wlm_user.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Mint(MintMsg { quantity: Some(u64::MAX), ..Default::default() }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::WhitelistAllowance { minted: 0 }
+    ));
+
+    // Batch must be paid in full
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg { quantity: Some(whitelist_allowance), ..Default::default() }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(whitelist_allowance - 1),
+        }],
+    );
+    assert!(res.is_err());
+
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg { quantity: Some(whitelist_allowance), ..Default::default() }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(whitelist_allowance),
+        }],
+    );
+    assert!(res.is_ok());
+
+    // wlm_user must own every token of the batch
+    for token_id in 1..=whitelist_allowance {
+        let owner_query: OwnerOfResponse = query(
+            &mut app,
+            nft.clone(),
+            Cw721QueryMsg::<Extension>::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(owner_query.owner, wlm_user.to_string());
+    }
+
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    // Batch sizes that overflow the count are sold out
+    let err = app
+        .execute_contract(
+            wlm_user.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Mint(MintMsg { quantity: Some(u64::MAX), ..Default::default() }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SoldOut {}
+    ));
+
+    // Only 3 tokens are left, so a batch of 4 must
+    // fail atomically
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg { quantity: Some(4), ..Default::default() }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(4_u64),
+        }],
+    );
+    assert!(res.is_err());
+    let num_tokens: NumTokensResponse =
+        query(&mut app, nft.clone(), Cw721QueryMsg::<Extension>::NumTokens {}).unwrap();
+    assert_eq!(num_tokens.count, whitelist_allowance);
+
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg { quantity: Some(3), ..Default::default() }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(3_u64),
+        }],
+    );
+    assert!(res.is_ok());
+}
+
+// Overpayment is refunded to the minter, while funds
+// that are too low or in another denom are rejected
+#[test]
+fn test_mint_payment() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+    mint_native(&mut app, public_user.to_string(), Uint128::from(10000_u128), "uatom");
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price);
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    // Underpaying returns InsufficientFunds
+    let err = app
+        .execute_contract(
+            public_user.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Mint(MintMsg::default()),
+            &[Coin {
+                denom: DENOM.to_string(),
+                amount: Uint128::from(9999_u128),
+            }],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientFunds { required, sent }
+            if required == price && sent == Uint128::from(9999_u128)
+    ));
+
+    // Coins of any other denom are rejected
+    let err = app
+        .execute_contract(
+            public_user.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Mint(MintMsg::default()),
+            &[
+                Coin {
+                    denom: DENOM.to_string(),
+                    amount: price,
+                },
+                Coin {
+                    denom: "uatom".to_string(),
+                    amount: Uint128::from(10000_u128),
+                },
+            ],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnexpectedFunds { denom } if denom == "uatom"
+    ));
+
+    // Overpayment is sent back to public_user
+    let balance_before = bank_query(&app, &public_user, DENOM);
+    app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(3_u64),
+        }],
+    )
+    .unwrap();
+    let balance_after = bank_query(&app, &public_user, DENOM);
+    assert_eq!(balance_before.amount - balance_after.amount, price);
+    assert_eq!(bank_query(&app, &wlm, DENOM).amount, price);
+}
+
+// Burning a token must not lead to its id, or any
+// other minted id, being minted again
+#[test]
+fn test_mint_after_burn() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // public_user mints and burns nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price);
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg { quantity: Some(2), ..Default::default() }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(2_u64),
+        }],
+    )
+    .unwrap();
+
+    // public_user burns the first token
+    app.execute_contract(
+        public_user.clone(),
+        nft.clone(),
+        &crate::token::ExecuteMsg::Burn {
+            token_id: "1".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Next mint continues from the minted count
+    let res = app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price,
+        }],
+    );
+    assert!(res.is_ok());
+    let owner_query: OwnerOfResponse = query(
+        &mut app,
+        nft.clone(),
+        Cw721QueryMsg::<Extension>::OwnerOf {
+            token_id: "3".to_string(),
+            include_expired: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(owner_query.owner, public_user.to_string());
+
+    let minted: MintedCount = query(&mut app, wlm.clone(), QueryMsg::MintedCount {}).unwrap();
+    assert_eq!(minted.total, 3);
+    assert_eq!(minted.public, 3);
+    assert_eq!(minted.private_whitelist, 0);
+}
+
+// With shuffled token ids, selling out the entire
+// collection mints every id in 1..=supply exactly once
+#[test]
+fn test_mint_all_nfts_shuffled() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // wlm_user mints the whole collection
+    let wlm_user = Addr::unchecked("some_customer");
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    mint_native(
+        &mut app,
+        wlm_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let config_update = State {
+        shuffle_token_ids: true,
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    // Collect the token ids reported by the minter
+    let mut minted_ids: Vec<u64> = vec![];
+    for _i in 0..supply {
+        let res = app
+            .execute_contract(
+                wlm_user.clone(),
+                wlm.clone(),
+                &ExecuteMsg::Mint(MintMsg::default()),
+                &[Coin {
+                    denom: DENOM.to_string(),
+                    amount: price,
+                }],
+            )
+            .unwrap();
+        let minter_event = res
+            .events
+            .iter()
+            .find(|event| {
+                event.ty == "wasm"
+                    && event
+                        .attributes
+                        .iter()
+                        .any(|attr| attr.key == "_contract_addr" && attr.value == wlm.as_str())
+            })
+            .unwrap();
+        let token_id = minter_event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "token_id")
+            .unwrap();
+        minted_ids.push(token_id.value.parse().unwrap());
+    }
+
+    // Ids are not handed out in order
+    let sequential: Vec<u64> = (1..=supply).collect();
+    assert_ne!(minted_ids, sequential);
+
+    // Every id is minted exactly once
+    minted_ids.sort_unstable();
+    assert_eq!(minted_ids, sequential);
+    let num_tokens: NumTokensResponse =
+        query(&mut app, nft.clone(), Cw721QueryMsg::<Extension>::NumTokens {}).unwrap();
+    assert_eq!(num_tokens.count, supply);
+
+    // Minting more than total supply must fail
+    let res = app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price,
+        }],
+    );
+    assert!(res.is_err());
+}
+
+// Whitelist members approved with their own allowance and
+// tier mint that many tokens at the tier price
+#[test]
+fn test_whitelist_tiers() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // og_user holds an OG spot
+    let og_user = Addr::unchecked("og_customer");
+    // wlm_user holds a regular allowlist spot
+    let wlm_user = Addr::unchecked("wlm_customer");
+
+    for user in [&og_user, &wlm_user] {
+        mint_native(
+            &mut app,
+            user.to_string(),
+            Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+            DENOM,
+        );
+    }
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let og_price = Uint128::from(5000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        whitelist_tiers: vec![WhitelistTier {
+            name: "og".to_string(),
+            price: og_price,
+        }],
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    // Members can only join existing tiers
+    let res = app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::PrivateWhitelistApprove(WhitelistApproveMsg {
+            whitelist_members: vec![WhitelistEntry {
+                address: og_user.to_string(),
+                allowance: Some(3),
+                tier: Some("whale".to_string()),
+            }],
+        }),
+        &[],
+    );
+    assert!(res.is_err());
+
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::PrivateWhitelistApprove(WhitelistApproveMsg {
+            whitelist_members: vec![
+                WhitelistEntry {
+                    address: og_user.to_string(),
+                    allowance: Some(3),
+                    tier: Some("og".to_string()),
+                },
+                WhitelistEntry {
+                    address: wlm_user.to_string(),
+                    allowance: Some(1),
+                    tier: None,
+                },
+            ],
+        }),
+        &[],
+    )
+    .unwrap();
+
+    // Tiers are named once, and can't be removed while
+    // members are assigned to them
+    let set_tiers = |names: &[&str]| {
+        ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+            whitelist_tiers: Some(
+                names
+                    .iter()
+                    .map(|name| WhitelistTier {
+                        name: name.to_string(),
+                        price: og_price,
+                    })
+                    .collect(),
+            ),
+            force: Some(true),
+            ..Default::default()
+        })
+    };
+    let err = app
+        .execute_contract(wlm_admin.clone(), wlm.clone(), &set_tiers(&["og", "whale", "og"]), &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DuplicateTier { tier } if tier == "og"
+    ));
+    let err = app
+        .execute_contract(wlm_admin.clone(), wlm.clone(), &set_tiers(&["whale"]), &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TierInUse { tier } if tier == "og"
+    ));
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &set_tiers(&["og", "whale"]), &[])
+        .unwrap();
+
+    let mint = |quantity: u64| ExecuteMsg::Mint(MintMsg {
+        quantity: Some(quantity),
+        ..Default::default()
+    });
+    let funds = |amount: Uint128| [Coin {
+        denom: DENOM.to_string(),
+        amount,
+    }];
+
+    // og_user mints 3 tokens at the og price
+    let res = app.execute_contract(og_user.clone(), wlm.clone(), &mint(3), &funds(og_price * Uint128::from(3_u64)));
+    assert!(res.is_ok());
+    let err = app
+        .execute_contract(og_user.clone(), wlm.clone(), &mint(1), &funds(og_price))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::WhitelistAllowance { minted: 3 }
+    ));
+
+    // wlm_user pays the private whitelist price for 1 token
+    let res = app.execute_contract(wlm_user.clone(), wlm.clone(), &mint(1), &funds(og_price));
+    assert!(res.is_err());
+    let res = app.execute_contract(wlm_user.clone(), wlm.clone(), &mint(1), &funds(price));
+    assert!(res.is_ok());
+    let res = app.execute_contract(wlm_user.clone(), wlm.clone(), &mint(1), &funds(price));
+    assert!(res.is_err());
+}
+
+// Eligibility reports, for the current phase, whether an
+// address can mint, at which price, and what it has left
+#[test]
+fn test_mint_eligibility() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // wlm_user is on the private whitelist
+    let wlm_user = Addr::unchecked("wlm_customer");
+    // public_user is on no whitelist
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        wlm_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let whitelist_allowance: u64 = 2;
+    let price = Uint128::from(10000_u128);
+    let private_whitelist_price = Uint128::from(8000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        whitelist_allowance,
+        vec![wlm_user.clone()],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let eligibility = |app: &mut cw_multi_test::App, address: &Addr| -> MintEligibility {
+        query(
+            app,
+            wlm.clone(),
+            QueryMsg::MintEligibility {
+                address: address.clone(),
+            },
+        )
+        .unwrap()
+    };
+
+    // Nobody can mint before the contract is initialized
+    let res = eligibility(&mut app, &wlm_user);
+    assert!(!res.can_mint);
+    assert_eq!(res.reason, Some("Minting has not started".to_string()));
+
+    let config_update = State {
+        private_whitelist_price,
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, whitelist_allowance, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    let res = eligibility(&mut app, &wlm_user);
+    assert!(res.can_mint);
+    assert_eq!(res.price, private_whitelist_price);
+    assert!(res.private_whitelist.listed);
+    assert_eq!(res.private_whitelist.remaining, whitelist_allowance);
+
+    let res = eligibility(&mut app, &public_user);
+    assert!(!res.can_mint);
+    assert_eq!(res.reason, Some("Not whitelisted".to_string()));
+
+    // wlm_user uses up the private whitelist allowance
+    app.execute_contract(
+        wlm_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg {
+            quantity: Some(whitelist_allowance),
+            ..Default::default()
+        }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: private_whitelist_price * Uint128::from(whitelist_allowance),
+        }],
+    )
+    .unwrap();
+    let res = eligibility(&mut app, &wlm_user);
+    assert!(!res.can_mint);
+    assert_eq!(res.private_whitelist.minted, whitelist_allowance);
+    assert_eq!(res.private_whitelist.remaining, 0);
+    assert_eq!(res.reason, Some("Whitelist allowance exceeded".to_string()));
+
+    // Everyone can mint at the public price once public
+    // minting is enabled
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+    let res = eligibility(&mut app, &public_user);
+    assert!(res.can_mint);
+    assert_eq!(res.price, price);
+    assert!(!res.public_whitelist.listed);
+}
+
+// Platform fee set at instantiate is sent to the platform
+// from every mint payment, and only wlm_admin can move it
+#[rstest]
+#[case(FeeAmount::Bps(500), 500)]
+#[case(FeeAmount::Flat(Uint128::from(300_u128)), 600)]
+#[case(FeeAmount::Flat(Uint128::from(20000_u128)), 2000)]
+fn test_platform_fee(#[case] amount: FeeAmount, #[case] expected_fee: u128) {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // platform receives the platform fee
+    let platform = Addr::unchecked("platform");
+    let new_platform = Addr::unchecked("new_platform");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(1000_u128);
+    let platform_fee = PlatformFee {
+        address: platform.clone(),
+        amount,
+    };
+    let mut msg = whitelist_minter_msg(&mut app, supply, 5, vec![], wlm_artist.clone(), price);
+    msg.platform_fee = Some(platform_fee_msg(&platform_fee));
+    let wlm = instantiate_whitelist_minter(&mut app, wlm_admin.clone(), &msg);
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.platform_fee, Some(platform_fee.clone()));
+
+    let config_update = State {
+        platform_fee: Some(platform_fee),
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    // public_user mints 2 nfts, the fee is never more than
+    // the payment
+    let res = app
+        .execute_contract(
+            public_user.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Mint(MintMsg {
+                quantity: Some(2),
+                ..Default::default()
+            }),
+            &[Coin {
+                denom: DENOM.to_string(),
+                amount: price * Uint128::from(2_u128),
+            }],
+        )
+        .unwrap();
+    let fee_attribute = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == "platform_fee")
+        .map(|attribute| attribute.value.clone());
+    assert_eq!(fee_attribute, Some(expected_fee.to_string()));
+    assert_eq!(bank_query(&app, &platform, DENOM).amount, Uint128::from(expected_fee));
+    assert_eq!(
+        bank_query(&app, &wlm, DENOM).amount,
+        Uint128::from(2000_u128 - expected_fee)
+    );
+
+    // Only wlm_admin can move the fee, not the artist
+    let update_address = ExecuteMsg::UpdatePlatformAddress(PlatformAddressMsg {
+        address: new_platform.to_string(),
+    });
+    let res = app.execute_contract(wlm_artist.clone(), wlm.clone(), &update_address, &[]);
+    assert!(res.is_err());
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &update_address, &[])
+        .unwrap();
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.platform_fee.unwrap().address, new_platform);
+}
+
+// Revenue raised in each phase, withdrawals and unique
+// minters are tracked, and withdrawals can't exceed the
+// contract balance
+#[test]
+fn test_sale_stats() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721 and withdraws the revenue
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // wlm_user mints whitelist nfts
+    let wlm_user = Addr::unchecked("wlm_customer");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    for user in [&wlm_user, &public_user] {
+        mint_native(
+            &mut app,
+            user.to_string(),
+            Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+            DENOM,
+        );
+    }
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let private_whitelist_price = Uint128::from(5000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![wlm_user.clone()],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        private_whitelist_price,
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    let mint = |quantity: u64| ExecuteMsg::Mint(MintMsg {
+        quantity: Some(quantity),
+        ..Default::default()
+    });
+    let funds = |amount: Uint128| [Coin {
+        denom: DENOM.to_string(),
+        amount,
+    }];
+
+    // wlm_user mints twice during the private whitelist
+    for _ in 0..2 {
+        app.execute_contract(wlm_user.clone(), wlm.clone(), &mint(1), &funds(private_whitelist_price))
+            .unwrap();
+    }
+
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(public_user.clone(), wlm.clone(), &mint(3), &funds(price * Uint128::from(3_u128)))
+        .unwrap();
+
+    // wlm_artist can't withdraw more than the balance
+    let raised = Uint128::from(40000_u128);
+    let err = app
+        .execute_contract(
+            wlm_artist.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Withdraw(WithdrawMsg {
+                amount: raised + Uint128::from(1_u128),
+                cw20: None,
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientBalance { available, .. } if available == raised
+    ));
+    app.execute_contract(
+        wlm_artist.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Withdraw(WithdrawMsg {
+            amount: Uint128::from(15000_u128),
+            cw20: None,
+        }),
+        &[],
+    )
+    .unwrap();
+
+    let stats: SaleStats = query(&mut app, wlm.clone(), QueryMsg::SaleStats {}).unwrap();
+    assert_eq!(
+        stats,
+        SaleStats {
+            raised: vec![
+                PhaseRaised {
+                    phase: "private_whitelist".to_string(),
+                    asset: DENOM.to_string(),
+                    amount: Uint128::from(10000_u128),
+                },
+                PhaseRaised {
+                    phase: "public".to_string(),
+                    asset: DENOM.to_string(),
+                    amount: Uint128::from(30000_u128),
+                },
+            ],
+            withdrawn: vec![AssetAmount {
+                asset: DENOM.to_string(),
+                amount: Uint128::from(15000_u128),
+            }],
+            unique_minters: 2,
+        }
+    );
+}
@@ -1,5 +1,17 @@
 pub mod util;
 
+mod config;
+mod cw20_payment;
+mod escrow;
 mod initialize;
+mod merkle;
+mod migrate;
 mod mint;
+mod ownership;
+mod pause;
+mod payees;
+mod reserved;
+mod schedule;
+mod voucher;
+mod whitelist;
 {{test_reveal}}
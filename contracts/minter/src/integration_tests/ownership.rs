@@ -0,0 +1,153 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Uint128};
+
+use cw_multi_test::Executor;
+
+use crate::integration_tests::util::{create_whitelist_minter, mock_app, query};
+use crate::msg::{AcceptRoleMsg, ExecuteMsg, QueryMsg, RenounceOwnershipMsg, TransferRoleMsg};
+use crate::query::Ownership;
+use crate::ContractError;
+
+// Owner and artist roles only move once the new address
+// accepts them
+#[test]
+fn test_two_step_transfers() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist receives the revenue
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    let new_admin = Addr::unchecked("new_owner");
+    let new_artist = Addr::unchecked("new_artist");
+
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        3333,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        Uint128::from(10000_u128),
+    );
+
+    let transfer = |address: &Addr| TransferRoleMsg { address: address.to_string() };
+
+    // Only the owner can propose a new owner
+    let err = app
+        .execute_contract(
+            new_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::TransferOwnership(transfer(&new_admin)),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::TransferOwnership(transfer(&new_admin)),
+        &[],
+    )
+    .unwrap();
+    let ownership: Ownership = query(&mut app, wlm.clone(), QueryMsg::Ownership {}).unwrap();
+    assert_eq!(ownership.owner, wlm_admin);
+    assert_eq!(ownership.pending_owner, Some(new_admin.clone()));
+
+    // Only the pending owner can accept
+    let err = app
+        .execute_contract(
+            wlm_artist.clone(),
+            wlm.clone(),
+            &ExecuteMsg::AcceptOwnership(AcceptRoleMsg {}),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    app.execute_contract(
+        new_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::AcceptOwnership(AcceptRoleMsg {}),
+        &[],
+    )
+    .unwrap();
+    let ownership: Ownership = query(&mut app, wlm.clone(), QueryMsg::Ownership {}).unwrap();
+    assert_eq!(ownership.owner, new_admin);
+    assert_eq!(ownership.pending_owner, None);
+
+    // The previous owner lost its admin rights
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::TransferOwnership(transfer(&wlm_admin)),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+
+    // The artist role moves the same way, from the artist
+    let err = app
+        .execute_contract(
+            new_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::TransferArtist(transfer(&new_artist)),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    app.execute_contract(
+        wlm_artist.clone(),
+        wlm.clone(),
+        &ExecuteMsg::TransferArtist(transfer(&new_artist)),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        new_artist.clone(),
+        wlm.clone(),
+        &ExecuteMsg::AcceptArtist(AcceptRoleMsg {}),
+        &[],
+    )
+    .unwrap();
+    let ownership: Ownership = query(&mut app, wlm.clone(), QueryMsg::Ownership {}).unwrap();
+    assert_eq!(ownership.artist, new_artist);
+    assert_eq!(ownership.pending_artist, None);
+
+    // Renouncing leaves the contract without an admin
+    app.execute_contract(
+        new_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::RenounceOwnership(RenounceOwnershipMsg {}),
+        &[],
+    )
+    .unwrap();
+    let ownership: Ownership = query(&mut app, wlm.clone(), QueryMsg::Ownership {}).unwrap();
+    assert_eq!(ownership.owner, wlm);
+    let err = app
+        .execute_contract(
+            new_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::TransferOwnership(transfer(&new_admin)),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+}
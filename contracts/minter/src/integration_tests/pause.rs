@@ -0,0 +1,229 @@
+#![cfg(test)]
+use cosmwasm_std::{to_json_binary, Addr, Coin, Uint128};
+
+use cw20::{Cw20Coin, Cw20ExecuteMsg};
+use cw_multi_test::Executor;
+
+use crate::integration_tests::util::{
+    bank_query, create_cw20, create_whitelist_minter, cw721_address, init_whitelist_minter,
+    mint_native, mock_app, query, sale_config, DENOM,
+};
+use crate::msg::{
+    DistributeMsg, EnablePublicMintMsg, ExecuteMsg, MintMsg, QueryMsg, ReceiveMsg,
+    RenounceOwnershipMsg, WithdrawMsg,
+};
+use crate::query::MintEligibility;
+use crate::state::{Cw20Price, Payee, QueriedState, State};
+use crate::ContractError;
+
+// Pausing halts minting and withdrawals without losing
+// the phase
+#[test]
+fn test_pause() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721 and receives the revenue
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price);
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::default());
+    let funds = [Coin {
+        denom: DENOM.to_string(),
+        amount: price,
+    }];
+    app.execute_contract(public_user.clone(), wlm.clone(), &mint_msg, &funds)
+        .unwrap();
+
+    // Only wlm_admin can pause
+    let err = app
+        .execute_contract(public_user.clone(), wlm.clone(), &ExecuteMsg::Pause {}, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    ));
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &ExecuteMsg::Pause {}, &[])
+        .unwrap();
+
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert!(config.paused);
+    assert!(config.public_mint);
+
+    // Minting and withdrawing are halted
+    let err = app
+        .execute_contract(public_user.clone(), wlm.clone(), &mint_msg, &funds)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+    let withdraw_msg = ExecuteMsg::Withdraw(WithdrawMsg {
+        amount: price,
+        cw20: None,
+    });
+    let err = app
+        .execute_contract(wlm_artist.clone(), wlm.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+
+    let eligibility: MintEligibility = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::MintEligibility {
+            address: public_user.clone(),
+        },
+    )
+    .unwrap();
+    assert!(!eligibility.can_mint);
+    assert_eq!(eligibility.reason, Some("Minting is paused".to_string()));
+
+    // Ownership can't be renounced, or nobody could unpause
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::RenounceOwnership(RenounceOwnershipMsg {}),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+
+    // Unpausing resumes the sale where it was
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &ExecuteMsg::Unpause {}, &[])
+        .unwrap();
+    app.execute_contract(public_user.clone(), wlm.clone(), &mint_msg, &funds)
+        .unwrap();
+    app.execute_contract(wlm_artist.clone(), wlm.clone(), &withdraw_msg, &[])
+        .unwrap();
+    assert_eq!(bank_query(&app, &wlm_artist, DENOM).amount, price);
+}
+
+// Distributing to payees and minting with cw20 tokens
+// are halted as well
+#[test]
+fn test_pause_distribute_and_receive() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721 and is paid out as a payee
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // public_user pays with cw20 tokens
+    let public_user = Addr::unchecked("public_customer");
+
+    let cw20 = create_cw20(
+        &mut app,
+        &wlm_admin,
+        vec![Cw20Coin {
+            address: public_user.to_string(),
+            amount: Uint128::from(1000_u128),
+        }],
+    );
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let cw20_price = Uint128::from(100_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        cw20_price: Some(Cw20Price {
+            address: cw20.clone(),
+            amount: cw20_price,
+        }),
+        payees: vec![Payee {
+            address: wlm_artist.clone(),
+            share_bps: 10000,
+        }],
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    let send = Cw20ExecuteMsg::Send {
+        contract: wlm.to_string(),
+        amount: cw20_price,
+        msg: to_json_binary(&ReceiveMsg::Mint(MintMsg::default())).unwrap(),
+    };
+    app.execute_contract(public_user.clone(), cw20.clone(), &send, &[])
+        .unwrap();
+
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &ExecuteMsg::Pause {}, &[])
+        .unwrap();
+
+    let err = app
+        .execute_contract(public_user.clone(), cw20.clone(), &send, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+    let distribute = ExecuteMsg::Distribute(DistributeMsg {
+        cw20: Some(cw20.clone()),
+    });
+    let err = app
+        .execute_contract(public_user.clone(), wlm.clone(), &distribute, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Paused {}
+    ));
+
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &ExecuteMsg::Unpause {}, &[])
+        .unwrap();
+    app.execute_contract(public_user.clone(), wlm.clone(), &distribute, &[])
+        .unwrap();
+}
@@ -0,0 +1,178 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Coin, Uint128};
+
+use cw_multi_test::Executor;
+
+use crate::integration_tests::util::{
+    bank_query, config_update_msg, create_whitelist_minter, cw721_address, init_whitelist_minter,
+    mint_native, mock_app, query, sale_config, DENOM,
+};
+use crate::msg::{DistributeMsg, EnablePublicMintMsg, ExecuteMsg, MintMsg, QueryMsg, WithdrawMsg};
+use crate::query::Payees;
+use crate::state::{Payee, State};
+use crate::ContractError;
+
+// Mint revenue is split among the payees pro rata to
+// their shares, and Withdraw is disabled meanwhile
+#[test]
+fn test_distribute() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721 and gets most of the revenue
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // platform and charity get the rest of the revenue
+    let platform = Addr::unchecked("platform");
+    let charity = Addr::unchecked("charity");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let payee = |address: &Addr, share_bps: u16| Payee {
+        address: address.clone(),
+        share_bps,
+    };
+    let config_update = State {
+        payees: vec![
+            payee(&wlm_artist, 7000),
+            payee(&platform, 2000),
+            payee(&charity, 1000),
+        ],
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+
+    // Shares must add up to the whole balance
+    let mut invalid_payees = config_update.clone();
+    invalid_payees.payees[2].share_bps = 500;
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(config_update_msg(invalid_payees)),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidPayees {}
+    ));
+
+    // Payees are named when their address is bad
+    let mut invalid_payees = config_update_msg(config_update.clone());
+    if let Some(payees) = invalid_payees.payees.as_mut() {
+        payees[2].address = "Not An Address".to_string();
+    }
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(invalid_payees),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAddress { address } if address == "Not An Address"
+    ));
+
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    // public_user mints 10 nfts
+    let quantity: u64 = 10;
+    let revenue = price * Uint128::from(quantity);
+    app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg {
+            quantity: Some(quantity),
+            ..Default::default()
+        }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: revenue,
+        }],
+    )
+    .unwrap();
+
+    // wlm_artist can't withdraw while payees are set
+    let err = app
+        .execute_contract(
+            wlm_artist.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Withdraw(WithdrawMsg {
+                amount: revenue,
+                cw20: None,
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PayeesConfigured {}
+    ));
+
+    let payees: Payees = query(&mut app, wlm.clone(), QueryMsg::Payees { cw20: None }).unwrap();
+    assert_eq!(payees.payees[1].address, platform);
+    assert_eq!(payees.payees[1].owed, Uint128::from(20000_u128));
+    assert_eq!(payees.payees[1].claimed, Uint128::zero());
+
+    // Only the cw20 minters pay with can be distributed
+    let err = app
+        .execute_contract(
+            public_user.clone(),
+            wlm.clone(),
+            &ExecuteMsg::Distribute(DistributeMsg {
+                cw20: Some(Addr::unchecked("other_cw20")),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnknownCw20 { address } if address == "other_cw20"
+    ));
+
+    // Anyone can trigger the distribution
+    app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Distribute(DistributeMsg { cw20: None }),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(bank_query(&app, &wlm_artist, DENOM).amount, Uint128::from(70000_u128));
+    assert_eq!(bank_query(&app, &platform, DENOM).amount, Uint128::from(20000_u128));
+    assert_eq!(bank_query(&app, &charity, DENOM).amount, Uint128::from(10000_u128));
+    assert_eq!(bank_query(&app, &wlm, DENOM).amount, Uint128::zero());
+
+    let payees: Payees = query(&mut app, wlm.clone(), QueryMsg::Payees { cw20: None }).unwrap();
+    assert_eq!(payees.payees[1].owed, Uint128::zero());
+    assert_eq!(payees.payees[1].claimed, Uint128::from(20000_u128));
+}
@@ -0,0 +1,262 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Coin, Uint128};
+
+use cw_multi_test::Executor;
+
+use crate::token::{Extension, QueryMsg as Cw721QueryMsg};
+use crate::token::cw721::OwnerOfResponse;
+
+use crate::integration_tests::util::{
+    config_update_msg, contract_whitelist_minter, create_whitelist_minter, cw721_address,
+    init_whitelist_minter, mint_native, mock_app, query, sale_config, whitelist_minter_msg, DENOM,
+};
+use crate::msg::{
+    ConfigUpdateMsg, EnablePublicMintMsg, ExecuteMsg, MintMsg, MintReservedMsg, QueryMsg,
+};
+use crate::state::{QueriedState, State};
+use crate::ContractError;
+
+// Admin mints the reserved and founders allocations for
+// free, at any phase, and public mints can never use up
+// the tokens held back for them
+#[test]
+fn test_mint_reserved() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721 and receives founders nfts
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // giveaway_winner receives a reserved nft
+    let giveaway_winner = Addr::unchecked("giveaway_winner");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 10;
+    let total_reserved: u64 = 3;
+    let total_reserved_founders: u64 = 1;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        total_reserved,
+        total_reserved_founders,
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::UpdateConfig(config_update_msg(config_update.clone())),
+        &[],
+    )
+    .unwrap();
+
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert_eq!(config.total_reserved, total_reserved);
+
+    // Allocations whose sum overflows are rejected
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+                total_reserved: Some(u64::MAX),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SourceMetadata {}
+    ));
+    let mut msg = whitelist_minter_msg(&mut app, supply, 5, vec![], wlm_artist.clone(), price);
+    msg.total_reserved = u64::MAX;
+    msg.total_reserved_founders = total_reserved_founders;
+    let wlm_id = app.store_code(contract_whitelist_minter());
+    let err = app
+        .instantiate_contract(wlm_id, wlm_admin.clone(), &msg, &[], "whitelist-minter", None)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SourceMetadata {}
+    ));
+    assert_eq!(config.total_reserved_founders, total_reserved_founders);
+
+    // Only wlm_admin can mint reserved nfts
+    let founders_mint = ExecuteMsg::MintReserved(MintReservedMsg {
+        founders: true,
+        quantity: 1,
+        recipients: vec![],
+    });
+    let res = app.execute_contract(public_user.clone(), wlm.clone(), &founders_mint, &[]);
+    assert!(res.is_err());
+
+    // Founders nfts are minted to the artist before the
+    // contract is even initialized
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &founders_mint, &[])
+        .unwrap();
+    let owner_query: OwnerOfResponse = query(
+        &mut app,
+        nft.clone(),
+        Cw721QueryMsg::<Extension>::OwnerOf {
+            token_id: "1".to_string(),
+            include_expired: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(owner_query.owner, wlm_artist.to_string());
+
+    // Founders allocation is used up
+    let res = app.execute_contract(wlm_admin.clone(), wlm.clone(), &founders_mint, &[]);
+    assert!(res.is_err());
+
+    // Batches whose size overflows are rejected
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::MintReserved(MintReservedMsg {
+                founders: false,
+                quantity: u64::MAX,
+                recipients: vec![giveaway_winner.to_string(), public_user.to_string()],
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidInput {}
+    ));
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::MintReserved(MintReservedMsg {
+                founders: true,
+                quantity: u64::MAX,
+                recipients: vec![],
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ReservedAllowance { minted: 1 }
+    ));
+
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    // Public mints stop once only reserved nfts are left
+    let public_supply = supply - total_reserved - total_reserved_founders;
+    let res = app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg { quantity: Some(public_supply), ..Default::default() }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(public_supply),
+        }],
+    );
+    assert!(res.is_ok());
+    let res = app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price,
+        }],
+    );
+    assert!(res.is_err());
+
+    // Recipients must be valid addresses
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::MintReserved(MintReservedMsg {
+                founders: false,
+                quantity: 1,
+                recipients: vec!["Not An Address".to_string()],
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAddress { address } if address == "Not An Address"
+    ));
+
+    // Reserved nfts can still be minted to given recipients
+    // without payment. A recipient listed twice gets a token
+    // for each entry
+    let res = app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::MintReserved(MintReservedMsg {
+            founders: false,
+            quantity: 1,
+            recipients: vec![
+                giveaway_winner.to_string(),
+                giveaway_winner.to_string(),
+                public_user.to_string(),
+            ],
+        }),
+        &[],
+    );
+    assert!(res.is_ok());
+    let owners = [
+        (supply - 2, &giveaway_winner),
+        (supply - 1, &giveaway_winner),
+        (supply, &public_user),
+    ];
+    for (token_id, owner) in owners {
+        let owner_query: OwnerOfResponse = query(
+            &mut app,
+            nft.clone(),
+            Cw721QueryMsg::<Extension>::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(owner_query.owner, owner.to_string());
+    }
+
+    // Reserved allocation is used up
+    let res = app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::MintReserved(MintReservedMsg {
+            founders: false,
+            quantity: 1,
+            recipients: vec![],
+        }),
+        &[],
+    );
+    assert!(res.is_err());
+}
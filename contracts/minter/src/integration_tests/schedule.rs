@@ -0,0 +1,178 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Coin, Uint128};
+
+use cw_multi_test::Executor;
+
+use crate::integration_tests::util::{
+    config_update_msg, create_whitelist_minter, cw721_address, mint_native, mock_app, query,
+    sale_config, DENOM,
+};
+use crate::msg::{EnablePublicMintMsg, ExecuteMsg, InitMsg, MintMsg, QueryMsg};
+use crate::state::{PhaseSchedule, QueriedState, State};
+
+// Phases with a scheduled start time must be enabled
+// from the block time, without any admin transaction
+#[test]
+fn test_scheduled_phases() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // wlm_user mints whitelist nfts
+    let wlm_user = Addr::unchecked("wlm_customer");
+    // public_user mints non-whitelist nfts
+    let public_user = Addr::unchecked("public_customer");
+
+    for user in [&wlm_user, &public_user] {
+        mint_native(
+            &mut app,
+            user.to_string(),
+            Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+            DENOM,
+        );
+    }
+
+    let supply: u64 = 3333;
+    let whitelist_allowance: u64 = 5;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        whitelist_allowance,
+        vec![wlm_user.clone()],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    // wlm_admin schedules the private whitelist and the
+    // public mint, but never broadcasts Initialize
+    let now = app.block_info().time;
+    let schedule = PhaseSchedule {
+        private_whitelist: Some(now.plus_seconds(100)),
+        normal_whitelist: None,
+        public: Some(now.plus_seconds(200)),
+        reveal: None,
+    };
+    let config_update = State {
+        schedule,
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, whitelist_allowance, price)
+    };
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::UpdateConfig(config_update_msg(config_update)),
+        &[],
+    )
+    .unwrap();
+
+    let funds = [Coin {
+        denom: DENOM.to_string(),
+        amount: price,
+    }];
+
+    // Minting must fail before the first scheduled phase
+    let res = app.execute_contract(wlm_user.clone(), wlm.clone(), &ExecuteMsg::Mint(MintMsg::default()), &funds);
+    assert!(res.is_err());
+
+    // Private whitelist starts from block time
+    app.update_block(|block| block.time = now.plus_seconds(100));
+    let res = app.execute_contract(wlm_user.clone(), wlm.clone(), &ExecuteMsg::Mint(MintMsg::default()), &funds);
+    assert!(res.is_ok());
+    let res = app.execute_contract(public_user.clone(), wlm.clone(), &ExecuteMsg::Mint(MintMsg::default()), &funds);
+    assert!(res.is_err());
+
+    // Public mint starts from block time
+    app.update_block(|block| block.time = now.plus_seconds(200));
+    let config: QueriedState = query(&mut app, wlm.clone(), QueryMsg::Config {}).unwrap();
+    assert!(config.public_mint);
+    let res = app.execute_contract(public_user.clone(), wlm.clone(), &ExecuteMsg::Mint(MintMsg::default()), &funds);
+    assert!(res.is_ok());
+}
+
+// Admin can still enable a phase before its scheduled start
+#[test]
+fn test_manual_phase_override() {
+    let mut app = mock_app();
+
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    let public_user = Addr::unchecked("public_customer");
+    mint_native(
+        &mut app,
+        public_user.to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let now = app.block_info().time;
+    let config_update = State {
+        schedule: PhaseSchedule {
+            public: Some(now.plus_seconds(1000)),
+            ..Default::default()
+        },
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+
+    // Schedule must not go backwards
+    let mut invalid_schedule = config_update.clone();
+    invalid_schedule.schedule.private_whitelist = Some(now.plus_seconds(2000));
+    let res = app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::UpdateConfig(config_update_msg(invalid_schedule)),
+        &[],
+    );
+    assert!(res.is_err());
+
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::UpdateConfig(config_update_msg(config_update)),
+        &[],
+    )
+    .unwrap();
+
+    // wlm_admin starts the sale early
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Initialize(InitMsg {}),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::EnablePublicMint(EnablePublicMintMsg {}),
+        &[],
+    )
+    .unwrap();
+
+    let res = app.execute_contract(
+        public_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price,
+        }],
+    );
+    assert!(res.is_ok());
+}
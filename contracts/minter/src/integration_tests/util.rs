@@ -5,15 +5,18 @@ use cosmwasm_std::{
     from_binary, to_json_binary, Addr, BalanceResponse as BalanceResponseBank, BankQuery, Coin, Empty,
     Querier, QueryRequest, StdError, Uint128, WasmQuery,
 };
+use cw20::Cw20Coin;
+use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
 use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
 
-use crate::token::InstantiateMsg as Cw721InstantiateMsg;
-
-use crate::contract::DENOM;
-use crate::msg::{ExecuteMsg, InitMsg, InstantiateMsg};
-use crate::state::State;
+use crate::msg::{
+    ConfigUpdateMsg, Cw20PriceMsg, ExecuteMsg, InitMsg, InstantiateMsg, PayeeMsg, PlatformFeeMsg,
+    QueryMsg,
+};
+use crate::state::{PlatformFee, QueriedState, State};
 
 pub static NAME_PREFIX: &str = "Token #";
+pub static DENOM: &str = "aarch";
 
 pub fn mock_app() -> App {
     App::default()
@@ -24,7 +27,8 @@ pub fn contract_whitelist_minter() -> Box<dyn Contract<Empty>> {
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_reply(crate::contract::reply);
     Box::new(contract)
 }
 
@@ -37,26 +41,64 @@ pub fn create_whitelist_minter(
     reserved_recipient: Addr,
     price: Uint128,
 ) -> Addr {
-    let wlm_id = router.store_code(contract_whitelist_minter());
+    let msg = whitelist_minter_msg(
+        router,
+        supply,
+        whitelist_allowance,
+        whitelist_members,
+        reserved_recipient,
+        price,
+    );
+    instantiate_whitelist_minter(router, owner, &msg)
+}
 
-    let cw721 = Addr::unchecked("temp_value"); // Must be updated to actual cw721 contract
-                                               // after both contracts have been deployed
-                                               // otherwise ExecuteMsg::Initialize will fail
+pub fn whitelist_minter_msg(
+    router: &mut App,
+    supply: u64,
+    whitelist_allowance: u64,
+    whitelist_members: Vec<Addr>,
+    reserved_recipient: Addr,
+    price: Uint128,
+) -> InstantiateMsg {
+    // The minter instantiates its own cw721 from this code
+    let cw721_id = router.store_code(contract_cw721());
 
-    let msg = InstantiateMsg {
-        cw721,
+    let whitelist_members: Vec<String> = whitelist_members.iter().map(Addr::to_string).collect();
+    InstantiateMsg {
+        token_code_id: cw721_id,
+        token_name: "TESTNFT".to_string(),
+        token_symbol: "TEST".to_string(),
         supply,
         public_whitelist_allowance: whitelist_allowance,
         public_whitelist_members: whitelist_members.clone(),
         private_whitelist_allowance: whitelist_allowance,
-        reserved_recipient,
+        total_reserved: 0,
+        total_reserved_founders: 0,
+        shuffle_token_ids: false,
+        reserved_recipient: reserved_recipient.to_string(),
         price,
+        private_whitelist_price: None,
+        public_whitelist_price: None,
+        whitelist_tiers: vec![],
+        whitelist_deposit: None,
+        denom: DENOM.to_string(),
+        cw20_price: None,
         naming_prefix: NAME_PREFIX.to_string(),
+        payees: vec![],
+        platform_fee: None,
         private_whitelist_members: whitelist_members,
-    };
+        schedule: None,
+        private_whitelist_merkle_root: None,
+        public_whitelist_merkle_root: None,
+        voucher_signer: None,
+        escrow: None,
+    }
+}
 
+pub fn instantiate_whitelist_minter(router: &mut App, owner: Addr, msg: &InstantiateMsg) -> Addr {
+    let wlm_id = router.store_code(contract_whitelist_minter());
     router
-        .instantiate_contract(wlm_id, owner.clone(), &msg, &[], "whitelist-minter", None)
+        .instantiate_contract(wlm_id, owner, msg, &[], "whitelist-minter", None)
         .unwrap()
 }
 
@@ -66,11 +108,11 @@ pub fn init_whitelist_minter(
     wlm_contract: Addr,
     config: State,
 ) -> AppResponse {
-    // Update State with correct cw721 contract address
-    let config_msg = ExecuteMsg::UpdateConfig { config };
-    let _config_res =
-        router.execute_contract(owner.clone(), wlm_contract.clone(), &config_msg, &[]);
-    // dbg!(_config_res);
+    // Apply the sale config
+    let config_msg = ExecuteMsg::UpdateConfig(config_update_msg(config));
+    router
+        .execute_contract(owner.clone(), wlm_contract.clone(), &config_msg, &[])
+        .unwrap();
 
     // Init contract and open for whitelist minting
     let msg = ExecuteMsg::Initialize(InitMsg {});
@@ -79,19 +121,124 @@ pub fn init_whitelist_minter(
     res.unwrap()
 }
 
-pub fn create_cw721(router: &mut App, minter: &Addr) -> Addr {
-    let cw721_id = router.store_code(contract_cw721());
-    let msg = Cw721InstantiateMsg {
-        name: "TESTNFT".to_string(),
+// Sale config used by most tests, to be adjusted with
+// struct update syntax. Every phase is sold at `price`
+pub fn sale_config(
+    owner: &Addr,
+    cw721: &Addr,
+    artist: &Addr,
+    supply: u64,
+    whitelist_allowance: u64,
+    price: Uint128,
+) -> State {
+    State {
+        owner: owner.clone(),
+        cw721: cw721.clone(),
+        artist: artist.clone(),
+        supply,
+        phase: Default::default(),
+        paused: false,
+        schedule: Default::default(),
+        total_reserved: 0,
+        total_reserved_founders: 0,
+        shuffle_token_ids: false,
+        private_whitelist_allowance: whitelist_allowance,
+        public_whitelist_allowance: whitelist_allowance,
+        private_whitelist_price: price,
+        public_whitelist_price: price,
+        whitelist_tiers: vec![],
+        whitelist_deposit: Uint128::zero(),
+        price,
+        denom: DENOM.to_string(),
+        cw20_price: None,
+        name_prefix: NAME_PREFIX.to_string(),
+        payees: vec![],
+        platform_fee: None,
+        private_whitelist_merkle_root: None,
+        public_whitelist_merkle_root: None,
+        voucher_signer: None,
+        escrow: None,
+    }
+}
+
+// Sets every field of `config` that UpdateConfig can change
+pub fn config_update_msg(config: State) -> ConfigUpdateMsg {
+    ConfigUpdateMsg {
+        cw721: Some(config.cw721.to_string()),
+        supply: Some(config.supply),
+        schedule: Some(config.schedule),
+        total_reserved: Some(config.total_reserved),
+        total_reserved_founders: Some(config.total_reserved_founders),
+        shuffle_token_ids: Some(config.shuffle_token_ids),
+        private_whitelist_allowance: Some(config.private_whitelist_allowance),
+        public_whitelist_allowance: Some(config.public_whitelist_allowance),
+        private_whitelist_price: Some(config.private_whitelist_price),
+        public_whitelist_price: Some(config.public_whitelist_price),
+        whitelist_tiers: Some(config.whitelist_tiers),
+        whitelist_deposit: Some(config.whitelist_deposit),
+        price: Some(config.price),
+        denom: Some(config.denom),
+        name_prefix: Some(config.name_prefix),
+        payees: Some(
+            config
+                .payees
+                .iter()
+                .map(|payee| PayeeMsg {
+                    address: payee.address.to_string(),
+                    share_bps: payee.share_bps,
+                })
+                .collect(),
+        ),
+        cw20_price: Some(config.cw20_price.map(|price| Cw20PriceMsg {
+            address: price.address.to_string(),
+            amount: price.amount,
+        })),
+        private_whitelist_merkle_root: Some(config.private_whitelist_merkle_root),
+        public_whitelist_merkle_root: Some(config.public_whitelist_merkle_root),
+        voucher_signer: Some(config.voucher_signer),
+        escrow: Some(config.escrow),
+        force: None,
+    }
+}
+
+// Address of the cw721 instantiated by the wlm contract
+pub fn platform_fee_msg(platform_fee: &PlatformFee) -> PlatformFeeMsg {
+    PlatformFeeMsg {
+        address: platform_fee.address.to_string(),
+        amount: platform_fee.amount.clone(),
+    }
+}
+
+pub fn cw721_address(router: &mut App, wlm_contract: &Addr) -> Addr {
+    let config: QueriedState = query(router, wlm_contract.clone(), QueryMsg::Config {}).unwrap();
+    config.cw721
+}
+
+pub fn create_cw20(router: &mut App, owner: &Addr, initial_balances: Vec<Cw20Coin>) -> Addr {
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = Cw20InstantiateMsg {
+        name: "TESTCOIN".to_string(),
         symbol: "TEST".to_string(),
-        minter: String::from(minter),
+        decimals: 6,
+        initial_balances,
+        mint: None,
+        marketing: None,
     };
 
     router
-        .instantiate_contract(cw721_id, minter.clone(), &msg, &[], "cw721", None)
+        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "cw20", None)
         .unwrap()
 }
 
+pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
 pub fn contract_cw721() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(
         crate::token::entry::execute,
@@ -102,7 +249,7 @@ pub fn contract_cw721() -> Box<dyn Contract<Empty>> {
 }
 
 #[allow(dead_code)]
-pub fn mint_native(app: &mut App, beneficiary: String, amount: Uint128) {
+pub fn mint_native(app: &mut App, beneficiary: String, amount: Uint128, denom: &str) {
     app.sudo(cw_multi_test::SudoMsg::Bank(
         cw_multi_test::BankSudo::Mint {
             to_address: beneficiary,
@@ -127,7 +274,7 @@ where
 }
 
 #[allow(dead_code)]
-pub fn bank_query(app: &App, address: &Addr) -> Coin {
+pub fn bank_query(app: &App, address: &Addr, denom: &str) -> Coin {
     let req: QueryRequest<BankQuery> = QueryRequest::Bank(BankQuery::Balance {
         address: address.to_string(),
         denom: DENOM.to_string(),
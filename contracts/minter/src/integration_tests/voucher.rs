@@ -0,0 +1,197 @@
+#![cfg(test)]
+use cosmwasm_std::{to_json_binary, Addr, Binary, Coin, Uint128};
+
+use cw_multi_test::Executor;
+use k256::ecdsa::signature::Signer;
+use k256::ecdsa::{Signature, SigningKey};
+
+use crate::integration_tests::util::{
+    create_whitelist_minter, cw721_address, init_whitelist_minter, mint_native, mock_app,
+    sale_config, DENOM,
+};
+use crate::msg::{ExecuteMsg, MintMsg, MintVoucher, SignedMintVoucher};
+use crate::state::{Phase, State};
+use crate::ContractError;
+
+fn sign_voucher(key: &SigningKey, voucher: MintVoucher) -> SignedMintVoucher {
+    // k256 signs the sha256 of the message
+    let signature: Signature = key.sign(to_json_binary(&voucher).unwrap().as_slice());
+    SignedMintVoucher {
+        voucher,
+        signature: Binary::from(signature.to_bytes().as_slice()),
+    }
+}
+
+// Users outside of the stored whitelists mint with a
+// voucher signed by the backend, once per nonce
+#[test]
+fn test_mint_voucher() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // voucher_user was issued an allowlist spot off-chain
+    let voucher_user = Addr::unchecked("voucher_customer");
+    // other_user tries to redeem someone else's voucher
+    let other_user = Addr::unchecked("other_customer");
+
+    for user in [&voucher_user, &other_user] {
+        mint_native(
+            &mut app,
+            user.to_string(),
+            Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+            DENOM,
+        );
+    }
+
+    let signer = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let signer_pubkey = signer.verifying_key().to_encoded_point(true);
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let voucher_price = Uint128::from(5000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        voucher_signer: Some(Binary::from(signer_pubkey.as_bytes())),
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    // other_wlm is another sale trusting the same signer
+    let other_wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let other_nft = cw721_address(&mut app, &other_wlm);
+    let config_update = State {
+        voucher_signer: Some(Binary::from(signer_pubkey.as_bytes())),
+        ..sale_config(&wlm_admin, &other_nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), other_wlm.clone(), config_update);
+
+    let voucher = MintVoucher {
+        contract: wlm.clone(),
+        chain_id: app.block_info().chain_id,
+        address: voucher_user.clone(),
+        phase: Phase::PrivateWhitelist,
+        max_quantity: 2,
+        price: voucher_price,
+        expires: app.block_info().time.plus_seconds(600),
+        nonce: 1,
+    };
+    let mint = |quantity: u64, voucher: SignedMintVoucher| {
+        ExecuteMsg::Mint(MintMsg {
+            quantity: Some(quantity),
+            voucher: Some(voucher),
+            ..Default::default()
+        })
+    };
+    let funds = |quantity: u64| {
+        [Coin {
+            denom: DENOM.to_string(),
+            amount: voucher_price * Uint128::from(quantity),
+        }]
+    };
+
+    // Vouchers signed by any other key are rejected
+    let forger = SigningKey::from_slice(&[9u8; 32]).unwrap();
+    let forged = sign_voucher(&forger, voucher.clone());
+    let err = app
+        .execute_contract(voucher_user.clone(), wlm.clone(), &mint(1, forged), &funds(1))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidVoucher {}
+    ));
+
+    // Tampering with a signed voucher invalidates it
+    let mut tampered = sign_voucher(&signer, voucher.clone());
+    tampered.voucher.max_quantity = 5;
+    let res = app.execute_contract(voucher_user.clone(), wlm.clone(), &mint(1, tampered), &funds(1));
+    assert!(res.is_err());
+
+    // Vouchers only work for their address and up to
+    // their max quantity
+    let signed = sign_voucher(&signer, voucher.clone());
+    let res = app.execute_contract(other_user.clone(), wlm.clone(), &mint(1, signed.clone()), &funds(1));
+    assert!(res.is_err());
+    let res = app.execute_contract(voucher_user.clone(), wlm.clone(), &mint(3, signed.clone()), &funds(3));
+    assert!(res.is_err());
+
+    // Vouchers only work on the contract and chain they
+    // were signed for
+    let err = app
+        .execute_contract(
+            voucher_user.clone(),
+            other_wlm.clone(),
+            &mint(1, signed.clone()),
+            &funds(1),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidVoucher {}
+    ));
+    let other_chain = sign_voucher(
+        &signer,
+        MintVoucher {
+            chain_id: "other-chain".to_string(),
+            ..voucher.clone()
+        },
+    );
+    let err = app
+        .execute_contract(voucher_user.clone(), wlm.clone(), &mint(1, other_chain), &funds(1))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidVoucher {}
+    ));
+
+    // voucher_user mints at the voucher price without
+    // being on the private whitelist
+    let res = app.execute_contract(voucher_user.clone(), wlm.clone(), &mint(2, signed.clone()), &funds(2));
+    assert!(res.is_ok());
+
+    // Nonces can't be replayed
+    let err = app
+        .execute_contract(voucher_user.clone(), wlm.clone(), &mint(1, signed), &funds(1))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::VoucherUsed { nonce: 1 }
+    ));
+
+    // Expired vouchers are rejected
+    let expired = sign_voucher(
+        &signer,
+        MintVoucher {
+            nonce: 2,
+            ..voucher.clone()
+        },
+    );
+    app.update_block(|block| block.time = block.time.plus_seconds(600));
+    let err = app
+        .execute_contract(voucher_user.clone(), wlm.clone(), &mint(1, expired), &funds(1))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::VoucherExpired {}
+    ));
+}
@@ -0,0 +1,428 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, Coin, Uint128};
+
+use cw_multi_test::Executor;
+
+use crate::integration_tests::util::{
+    bank_query, create_whitelist_minter, cw721_address, init_whitelist_minter, mint_native,
+    mock_app, query, sale_config, DENOM,
+};
+use crate::msg::{
+    ConfigUpdateMsg, ExecuteMsg, MintMsg, QueryMsg, WhitelistAddMsg, WhitelistApplicationsMsg,
+    WhitelistApproveMsg, WhitelistEntry, WhitelistRemoveMsg,
+};
+use crate::query::{WhitelistApplications, WhitelistMembers};
+use crate::state::{State, WhitelistKind, WhitelistStats};
+use crate::ContractError;
+
+// Whitelist members can be listed page by page, and
+// counted along with what they have minted
+#[test]
+fn test_whitelist_queries() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // wlm_users are on the private whitelist
+    let wlm_users: Vec<Addr> = (0..5)
+        .map(|i| Addr::unchecked(format!("wlm_customer_{}", i)))
+        .collect();
+
+    mint_native(
+        &mut app,
+        wlm_users[0].to_string(),
+        Uint128::from(100000000000000000000_u128), // 100 ARCH as aarch
+        DENOM,
+    );
+
+    let supply: u64 = 3333;
+    let whitelist_allowance: u64 = 2;
+    let price = Uint128::from(10000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        whitelist_allowance,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = sale_config(
+        &wlm_admin,
+        &nft,
+        &wlm_artist,
+        supply,
+        whitelist_allowance,
+        price,
+    );
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    // wlm_admin bulk approves the private whitelist
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::PrivateWhitelistApprove(WhitelistApproveMsg {
+            whitelist_members: wlm_users
+                .iter()
+                .map(|address| WhitelistEntry {
+                    address: address.to_string(),
+                    allowance: None,
+                    tier: None,
+                })
+                .collect(),
+        }),
+        &[],
+    )
+    .unwrap();
+
+    // Bad addresses are rejected by name
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::PrivateWhitelistApprove(WhitelistApproveMsg {
+                whitelist_members: vec![WhitelistEntry {
+                    address: "Not An Address".to_string(),
+                    allowance: None,
+                    tier: None,
+                }],
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAddress { address } if address == "Not An Address"
+    ));
+
+    // Members approved again are reported as duplicates
+    let res = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::PrivateWhitelistApprove(WhitelistApproveMsg {
+                whitelist_members: vec![WhitelistEntry {
+                    address: wlm_users[1].to_string(),
+                    allowance: None,
+                    tier: None,
+                }],
+            }),
+            &[],
+        )
+        .unwrap();
+    let duplicates: Vec<String> = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .filter(|attribute| attribute.key == "duplicate")
+        .map(|attribute| attribute.value.clone())
+        .collect();
+    assert_eq!(duplicates, vec![wlm_users[1].to_string()]);
+
+    // The first member mints their full allowance
+    app.execute_contract(
+        wlm_users[0].clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg {
+            quantity: Some(whitelist_allowance),
+            ..Default::default()
+        }),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price * Uint128::from(whitelist_allowance),
+        }],
+    )
+    .unwrap();
+
+    // Members are listed in pages
+    let page: WhitelistMembers = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistMembers {
+            list: WhitelistKind::Private,
+            start_after: None,
+            limit: Some(3),
+        },
+    )
+    .unwrap();
+    assert_eq!(page.members.len(), 3);
+    assert_eq!(page.members[0].address, wlm_users[0]);
+    assert_eq!(page.members[0].minted, whitelist_allowance);
+    assert_eq!(page.members[0].allowance, whitelist_allowance);
+
+    let next_page: WhitelistMembers = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistMembers {
+            list: WhitelistKind::Private,
+            start_after: Some(page.members[2].address.clone()),
+            limit: Some(3),
+        },
+    )
+    .unwrap();
+    assert_eq!(next_page.members.len(), 2);
+    assert_eq!(next_page.members[1].address, wlm_users[4]);
+
+    let stats: WhitelistStats = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistStats {
+            list: WhitelistKind::Private,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        stats,
+        WhitelistStats {
+            member_count: 5,
+            total_minted: whitelist_allowance,
+            exhausted_count: 1,
+        }
+    );
+
+    // Removing a member takes them out of the counts, but not
+    // what they minted
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::PrivateWhitelistRemove(WhitelistRemoveMsg {
+            whitelist_members: vec![wlm_users[0].to_string()],
+        }),
+        &[],
+    )
+    .unwrap();
+    let stats: WhitelistStats = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistStats {
+            list: WhitelistKind::Private,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        stats,
+        WhitelistStats {
+            member_count: 4,
+            total_minted: whitelist_allowance,
+            exhausted_count: 0,
+        }
+    );
+
+    // Default allowance is fixed once the list has minted
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+                private_whitelist_allowance: Some(whitelist_allowance + 1),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ConfigLocked { field } if field == "private_whitelist_allowance"
+    ));
+
+    // Public whitelist is still empty
+    let stats: WhitelistStats = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistStats {
+            list: WhitelistKind::Public,
+        },
+    )
+    .unwrap();
+    assert_eq!(stats.member_count, 0);
+}
+
+// Users apply to a whitelist with a deposit. Approved
+// applicants join the whitelist they applied to, and
+// rejected ones get their deposit back
+#[test]
+fn test_whitelist_applications() {
+    let mut app = mock_app();
+
+    // wlm_admin deploys and owns the wlm contract
+    let wlm_admin = Addr::unchecked("whitelist_minter_owner");
+    // wlm_artist owns the cw721
+    let wlm_artist = Addr::unchecked("cw721_artist");
+    // approved_user applies to the private whitelist
+    let approved_user = Addr::unchecked("approved_customer");
+    // rejected_user applies to the public whitelist
+    let rejected_user = Addr::unchecked("rejected_customer");
+
+    let initial_balance = Uint128::from(100000000000000000000_u128); // 100 ARCH as aarch
+    for user in [&approved_user, &rejected_user] {
+        mint_native(&mut app, user.to_string(), initial_balance, DENOM);
+    }
+
+    let supply: u64 = 3333;
+    let price = Uint128::from(10000_u128);
+    let deposit = Uint128::from(1000_u128);
+    let wlm = create_whitelist_minter(
+        &mut app,
+        wlm_admin.clone(),
+        supply,
+        5,
+        vec![],
+        wlm_artist.clone(),
+        price,
+    );
+    let nft = cw721_address(&mut app, &wlm);
+
+    let config_update = State {
+        whitelist_deposit: deposit,
+        ..sale_config(&wlm_admin, &nft, &wlm_artist, supply, 5, price)
+    };
+    init_whitelist_minter(&mut app, wlm_admin.clone(), wlm.clone(), config_update);
+
+    let apply = |list: WhitelistKind| ExecuteMsg::WhitelistAdd(WhitelistAddMsg { list });
+    let funds = |amount: u128| [Coin {
+        denom: DENOM.to_string(),
+        amount: Uint128::from(amount),
+    }];
+
+    // Applications must pay the deposit
+    let res = app.execute_contract(approved_user.clone(), wlm.clone(), &apply(WhitelistKind::Private), &[]);
+    assert!(res.is_err());
+
+    // Anything paid above the deposit is sent back
+    app.execute_contract(approved_user.clone(), wlm.clone(), &apply(WhitelistKind::Private), &funds(1500))
+        .unwrap();
+    app.execute_contract(rejected_user.clone(), wlm.clone(), &apply(WhitelistKind::Public), &funds(1000))
+        .unwrap();
+    assert_eq!(bank_query(&app, &wlm, DENOM).amount, deposit * Uint128::from(2_u128));
+
+    // Only one application per address
+    let res = app.execute_contract(rejected_user.clone(), wlm.clone(), &apply(WhitelistKind::Private), &funds(1000));
+    assert!(res.is_err());
+
+    let pending: WhitelistApplications = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistApplications {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(pending.applications.len(), 2);
+    assert_eq!(pending.applications[0].applicant, approved_user);
+    assert_eq!(pending.applications[0].list, WhitelistKind::Private);
+    assert_eq!(pending.applications[0].deposit, deposit);
+
+    // Deposits are refunded in the denom they were paid in,
+    // so it can't change while any are pending
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::UpdateConfig(ConfigUpdateMsg {
+                denom: Some("uother".to_string()),
+                ..Default::default()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ConfigLocked { field } if field == "denom"
+    ));
+
+    // Applicants are named when their address is bad
+    let err = app
+        .execute_contract(
+            wlm_admin.clone(),
+            wlm.clone(),
+            &ExecuteMsg::WhitelistApplicationsApprove(WhitelistApplicationsMsg {
+                applicants: vec!["Not An Address".to_string()],
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAddress { address } if address == "Not An Address"
+    ));
+
+    // wlm_admin grants approved_user a larger allowance while
+    // the application is pending
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::PrivateWhitelistApprove(WhitelistApproveMsg {
+            whitelist_members: vec![WhitelistEntry {
+                address: approved_user.to_string(),
+                allowance: Some(8),
+                tier: None,
+            }],
+        }),
+        &[],
+    )
+    .unwrap();
+
+    // Only wlm_admin can review applications
+    let approve = ExecuteMsg::WhitelistApplicationsApprove(WhitelistApplicationsMsg {
+        applicants: vec![approved_user.to_string()],
+    });
+    let res = app.execute_contract(approved_user.clone(), wlm.clone(), &approve, &[]);
+    assert!(res.is_err());
+    app.execute_contract(wlm_admin.clone(), wlm.clone(), &approve, &[])
+        .unwrap();
+    app.execute_contract(
+        wlm_admin.clone(),
+        wlm.clone(),
+        &ExecuteMsg::WhitelistApplicationsReject(WhitelistApplicationsMsg {
+            applicants: vec![rejected_user.to_string()],
+        }),
+        &[],
+    )
+    .unwrap();
+
+    // Approving the application keeps the existing entry
+    let members: WhitelistMembers = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistMembers {
+            list: WhitelistKind::Private,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(members.members.len(), 1);
+    assert_eq!(members.members[0].address, approved_user);
+    assert_eq!(members.members[0].allowance, 8);
+
+    // approved_user can mint during the private whitelist
+    let res = app.execute_contract(
+        approved_user.clone(),
+        wlm.clone(),
+        &ExecuteMsg::Mint(MintMsg::default()),
+        &[Coin {
+            denom: DENOM.to_string(),
+            amount: price,
+        }],
+    );
+    assert!(res.is_ok());
+
+    // rejected_user got the deposit back
+    assert_eq!(bank_query(&app, &rejected_user, DENOM).amount, initial_balance);
+
+    let pending: WhitelistApplications = query(
+        &mut app,
+        wlm.clone(),
+        QueryMsg::WhitelistApplications {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert!(pending.applications.is_empty());
+}
@@ -1,5 +1,6 @@
 pub mod contract;
 pub mod execute;
+pub mod migrate;
 pub mod msg;
 pub mod query;
 pub mod state;
@@ -0,0 +1,105 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, DepsMut, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::Item;
+
+use crate::error::ContractError;
+use crate::state::{
+    Phase, ReservedMinted, State, WhitelistKind, WhitelistStats, MINTED_COUNT, RESERVED_MINTED, STATE,
+};
+
+// v1 contracts only sold on mainnet
+const V1_DENOM: &str = "aarch";
+
+/// Layout of `State` up to v1.0.1
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateV1 {
+    pub owner: Addr,
+    pub cw721: Addr,
+    pub artist: Addr,
+    pub supply: u64,
+    pub phase: Phase,
+
+    pub private_whitelist_allowance: u64,
+    pub public_whitelist_allowance: u64,
+    pub price: Uint128,
+    pub name_prefix: String,
+}
+
+pub const STATE_V1: Item<StateV1> = Item::new("state");
+
+/// Moves a v1 `State` into the current layout. v1 charged one
+/// price in every phase, and numbered tokens in order, so
+/// `minted_count` is the number of token ids it handed out
+pub fn migrate_from_v1(
+    deps: DepsMut,
+    denom: Option<String>,
+    minted_count: Option<u64>,
+) -> Result<(), ContractError> {
+    let legacy = STATE_V1.load(deps.storage)?;
+
+    // v1 kept no count, and the collection can't tell which
+    // ids were burned, the highest ones included, so it must
+    // be given
+    let minted_count = minted_count.ok_or(ContractError::MissingMintedCount {})?;
+
+    let state = State {
+        owner: legacy.owner,
+        cw721: legacy.cw721,
+        artist: legacy.artist,
+        supply: legacy.supply,
+        phase: legacy.phase,
+        paused: false,
+        schedule: Default::default(),
+        total_reserved: 0,
+        total_reserved_founders: 0,
+        shuffle_token_ids: false,
+        private_whitelist_allowance: legacy.private_whitelist_allowance,
+        public_whitelist_allowance: legacy.public_whitelist_allowance,
+        private_whitelist_price: legacy.price,
+        public_whitelist_price: legacy.price,
+        whitelist_tiers: vec![],
+        whitelist_deposit: Uint128::zero(),
+        price: legacy.price,
+        denom: denom.unwrap_or_else(|| V1_DENOM.to_string()),
+        cw20_price: None,
+        name_prefix: legacy.name_prefix,
+        payees: vec![],
+        platform_fee: None,
+        private_whitelist_merkle_root: None,
+        public_whitelist_merkle_root: None,
+        voucher_signer: None,
+        escrow: None,
+    };
+    STATE.save(deps.storage, &state)?;
+    MINTED_COUNT.save(deps.storage, &minted_count)?;
+    RESERVED_MINTED.save(deps.storage, &ReservedMinted::default())?;
+    for list in [WhitelistKind::Private, WhitelistKind::Public] {
+        seed_whitelist_stats(deps.storage, &state, list)?;
+    }
+
+    Ok(())
+}
+
+// v1 kept no whitelist stats, so they are counted once from
+// the stored lists
+fn seed_whitelist_stats(storage: &mut dyn Storage, state: &State, list: WhitelistKind) -> StdResult<()> {
+    let default_allowance = list.default_allowance(state);
+    let counter = list.counter();
+
+    let mut stats = WhitelistStats::default();
+    for item in list.members().range(storage, None, None, Order::Ascending) {
+        let (address, member) = item?;
+        let minted = counter.may_load(storage, &address)?.unwrap_or_default();
+        stats.member_count += 1;
+        if member.is_exhausted(minted, default_allowance) {
+            stats.exhausted_count += 1;
+        }
+    }
+    for item in counter.range(storage, None, None, Order::Ascending) {
+        let (_, minted) = item?;
+        stats.total_minted += minted;
+    }
+    list.stats().save(storage, &stats)
+}
@@ -1,9 +1,11 @@
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Timestamp, Uint128};
 
-use crate::state::State;
+use cw20::Cw20ReceiveMsg;
+
+use crate::state::{Escrow, FeeAmount, Phase, PhaseSchedule, WhitelistKind, WhitelistTier};
 
 // #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 // pub struct InstantiateMsg {
@@ -20,15 +22,41 @@ use crate::state::State;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    pub cw721: Addr,
+    // Token contract instantiated by the minter, with the
+    // minter as its minter
+    pub token_code_id: u64,
+    pub token_name: String,
+    pub token_symbol: String,
     pub supply: u64,
     pub public_whitelist_allowance: u64,
-    pub public_whitelist_members: Vec<Addr>,
+    pub public_whitelist_members: Vec<String>,
     pub private_whitelist_allowance: u64,
-    pub private_whitelist_members: Vec<Addr>,
-    pub reserved_recipient: Addr,
+    pub private_whitelist_members: Vec<String>,
+    pub total_reserved: u64,
+    pub total_reserved_founders: u64,
+    pub shuffle_token_ids: bool,
+    pub reserved_recipient: String,
     pub price: Uint128,
+    // Whitelist prices default to the public `price`
+    pub private_whitelist_price: Option<Uint128>,
+    pub public_whitelist_price: Option<Uint128>,
+    pub whitelist_tiers: Vec<WhitelistTier>,
+    // Paid with whitelist applications, defaults to none
+    pub whitelist_deposit: Option<Uint128>,
+    pub denom: String,
+    pub cw20_price: Option<Cw20PriceMsg>,
     pub naming_prefix: String,
+    // Shares must add up to 10000 basis points
+    pub payees: Vec<PayeeMsg>,
+    pub platform_fee: Option<PlatformFeeMsg>,
+    pub schedule: Option<PhaseSchedule>,
+    // Hex encoded sha256 roots of whitelists too large to store
+    pub private_whitelist_merkle_root: Option<String>,
+    pub public_whitelist_merkle_root: Option<String>,
+    // Secp256k1 public key signing mint vouchers
+    pub voucher_signer: Option<Binary>,
+    // Holds payments until a minimum is sold, refundable otherwise
+    pub escrow: Option<Escrow>,
 }
 
 
@@ -37,11 +65,15 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     Mint(MintMsg),
     Reveal(RevealMsg), // Only works for updatable
-    // WhitelistAdd(WhitelistAddMsg), // Added members must be approved by admin
+    Receive(Cw20ReceiveMsg), // Mint paid with cw20 tokens
+    WhitelistAdd(WhitelistAddMsg), // Added members must be approved by admin
+    Distribute(DistributeMsg), // Splits the balance among payees
+    ClaimRefund(ClaimRefundMsg), // Burns tokens of a failed escrow sale
     // Artist only
-    Withdraw(WithdrawMsg),
+    Withdraw(WithdrawMsg), // Only when no payees are set
     // Admin only
     Initialize(InitMsg),
+    MintReserved(MintReservedMsg), // Free mints from the reserved allocations
     EnableNormalWhitelist(EnableWhitelistMintMsg),
     EnablePublicMint(EnablePublicMintMsg),
     EnableReveal(EnableRevealMsg), // Only works for updatable
@@ -50,8 +82,29 @@ pub enum ExecuteMsg {
     PrivateWhitelistRemove(WhitelistRemoveMsg),   // Bulk remove WLM members
     PublicWhitelistApprove(WhitelistApproveMsg), // Bulk approve WLM members
     PublicWhitelistRemove(WhitelistRemoveMsg),   // Bulk remove WLM members
+    PrivateWhitelistMerkleRoot(MerkleRootMsg), // Rotate the private WLM root
+    PublicWhitelistMerkleRoot(MerkleRootMsg),  // Rotate the public WLM root
+    WhitelistApplicationsApprove(WhitelistApplicationsMsg), // Bulk add applicants to their WLM
+    WhitelistApplicationsReject(WhitelistApplicationsMsg),  // Bulk reject, refunding deposits
+
+    UpdatePlatformAddress(PlatformAddressMsg), // Platform fee terms are fixed
+    Pause {},   // Halts minting, reveals and payouts
+    Unpause {},
+    TransferOwnership(TransferRoleMsg), // Must be accepted by the new owner
+    AcceptOwnership(AcceptRoleMsg),
+    RenounceOwnership(RenounceOwnershipMsg), // Irreversible, disables admin messages
+    // Artist only
+    TransferArtist(TransferRoleMsg), // Must be accepted by the new artist
+    AcceptArtist(AcceptRoleMsg),
+
+    UpdateConfig(ConfigUpdateMsg),
+}
 
-    UpdateConfig { config: State },
+// Payloads accepted through the cw20 `Receive` hook
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Mint(MintMsg),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -60,14 +113,107 @@ pub enum QueryMsg {
     Config {},
     TokenStatuses { token_ids: Vec<String> }, // Only works for updatable
     Whitelist { address: Addr },
+    MintedCount {},
+    MintEligibility { address: Addr },
+    WhitelistMembers {
+        list: WhitelistKind,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    WhitelistStats { list: WhitelistKind },
+    WhitelistApplications {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    Payees { cw20: Option<Addr> },
+    SaleStats {},
+    Ownership {},
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    // Payment denom of contracts older than v1.1, which
+    // didn't store one. Defaults to "aarch"
+    pub denom: Option<String>,
+    // Tokens minted by contracts older than v1.1, burned ones
+    // included. Required when migrating from them
+    pub minted_count: Option<u64>,
+    // Applied after the migration steps, with the same rules
+    // as UpdateConfig
+    pub config: Option<ConfigUpdateMsg>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct MintMsg {
+    // Number of sequential tokens to mint, defaults to 1
+    pub quantity: Option<u64>,
+    // Merkle proof of the sender in the current whitelist
+    pub proof: Option<Vec<String>>,
+    // Allowance committed in the sender's leaf, if any
+    pub allowance: Option<u64>,
+    // Signed off-chain, replaces the whitelist checks
+    pub voucher: Option<SignedMintVoucher>,
+}
+
+// Allowlist spot issued off-chain by the holder of
+// `State.voucher_signer`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintVoucher {
+    // Minter contract, and chain, the voucher is redeemable on
+    pub contract: Addr,
+    pub chain_id: String,
+    pub address: Addr,
+    pub phase: Phase,
+    pub max_quantity: u64,
+    // Per token, in the native denom
+    pub price: Uint128,
+    pub expires: Timestamp,
+    // Each nonce can only be redeemed once
+    pub nonce: u64,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MintMsg {}
+pub struct SignedMintVoucher {
+    pub voucher: MintVoucher,
+    // Signature of the sha256 of the json encoded voucher
+    pub signature: Binary,
+}
+
+/// `Cw20Price` as sent in messages, with its cw20 address
+/// not yet validated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw20PriceMsg {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+/// `Payee` as sent in messages, with its address not yet
+/// validated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayeeMsg {
+    pub address: String,
+    pub share_bps: u16,
+}
+
+/// `PlatformFee` as sent in messages, with its address not
+/// yet validated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlatformFeeMsg {
+    pub address: String,
+    pub amount: FeeAmount,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintReservedMsg {
+    // Mints from `total_reserved_founders` instead of `total_reserved`
+    pub founders: bool,
+    // Tokens minted to each recipient
+    pub quantity: u64,
+    // Defaults to the artist when empty. An address listed
+    // twice receives `quantity` tokens for each entry
+    pub recipients: Vec<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct EnableRevealMsg {}
@@ -86,20 +232,124 @@ pub struct RevealMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WithdrawMsg {
     pub amount: Uint128,
+    // Withdraws from this cw20 balance instead of the native denom
+    pub cw20: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DistributeMsg {
+    // Distributes this cw20 balance instead of the native denom
+    pub cw20: Option<Addr>,
+}
+
+// Refunded tokens are burned, so their holder must first
+// approve the minter on the cw721, with Approve or ApproveAll
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRefundMsg {
+    pub token_ids: Vec<String>,
+}
+
+// Fields left unset keep their current value. Phase, owner,
+// artist and platform fee have their own messages
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigUpdateMsg {
+    // Fixed once minting has begun
+    pub cw721: Option<String>,
+    // Can't drop below the minted count
+    pub supply: Option<u64>,
+    pub schedule: Option<PhaseSchedule>,
+    pub total_reserved: Option<u64>,
+    pub total_reserved_founders: Option<u64>,
+    pub shuffle_token_ids: Option<bool>,
+    pub private_whitelist_allowance: Option<u64>,
+    pub public_whitelist_allowance: Option<u64>,
+    pub private_whitelist_price: Option<Uint128>,
+    pub public_whitelist_price: Option<Uint128>,
+    pub whitelist_tiers: Option<Vec<WhitelistTier>>,
+    pub whitelist_deposit: Option<Uint128>,
+    pub price: Option<Uint128>,
+    pub denom: Option<String>,
+    pub name_prefix: Option<String>,
+    pub payees: Option<Vec<PayeeMsg>>,
+    // Optional settings are cleared when sent as null
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub cw20_price: Option<Option<Cw20PriceMsg>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub private_whitelist_merkle_root: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub public_whitelist_merkle_root: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub voucher_signer: Option<Option<Binary>>,
+    // Fixed once minting has begun
+    #[serde(default, deserialize_with = "nullable", skip_serializing_if = "Option::is_none")]
+    pub escrow: Option<Option<Escrow>>,
+    // Allows price changes while a phase is minting
+    pub force: Option<bool>,
+}
+
+// Reads a present field, null included, as `Some`, so a
+// missing field (`None`) can be told apart from a cleared one
+// (`Some(None)`)
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferRoleMsg {
+    pub address: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AcceptRoleMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RenounceOwnershipMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlatformAddressMsg {
+    pub address: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {}
 
-// #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-// pub struct WhitelistAddMsg {}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistAddMsg {
+    // Whitelist the sender applies to
+    pub list: WhitelistKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistApplicationsMsg {
+    pub applicants: Vec<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistApproveMsg {
-    pub whitelist_members: Vec<Addr>,
+    pub whitelist_members: Vec<WhitelistEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistEntry {
+    pub address: String,
+    // Defaults to the whitelist allowance from `State`
+    pub allowance: Option<u64>,
+    // Defaults to the price of the whitelist phase
+    pub tier: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistRemoveMsg {
-    pub whitelist_members: Vec<Addr>,
+    pub whitelist_members: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleRootMsg {
+    // Clearing the root leaves only stored members
+    pub merkle_root: Option<String>,
 }
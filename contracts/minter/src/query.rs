@@ -1,13 +1,16 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Deps, QueryRequest, StdResult, to_json_binary, WasmQuery};
+use cosmwasm_std::{Addr, Deps, Env, Order, QueryRequest, StdResult, to_json_binary, Uint128, WasmQuery};
+use cw_storage_plus::{Bound, Map};
 use crate::ContractError;
 
 use crate::token::{Extension, QueryMsg as Cw721QueryMsg};
 use crate::token::cw721::NftInfoResponse;
 
-use crate::state::{State, WhitelistMember, STATE, PUBLIC_WHITELIST, QueriedState};
+use crate::execute::{asset_key, distributable_balance, escrow_status, mint_price};
+use crate::msg::MintMsg;
+use crate::state::{EscrowStatus, Phase, State, WhitelistKind, WhitelistMember, WhitelistStats, WHITELIST_APPLICATIONS, PAYEE_CLAIMED, PENDING_ARTIST, PENDING_OWNER, PHASE_RAISED, UNIQUE_MINTERS, WITHDRAWN, STATE, MINTED_COUNT, PHASE_MINTED, PUBLIC_WHITELIST, PUBLIC_WHITELIST_COUNTER, PRIVATE_WHITELIST, PRIVATE_WHITELIST_COUNTER, RESERVED_MINTED, QueriedState};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TokenStatus {
@@ -22,8 +25,119 @@ pub struct TokenStatuses {
     pub unrevealed: Vec<TokenStatus>,
 }
 
-pub fn query_config(deps: Deps) -> Result<QueriedState, ContractError> {
-    let config: State = STATE.load(deps.storage)?;
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintedCount {
+    pub total: u64,
+    pub private_whitelist: u64,
+    pub normal_whitelist: u64,
+    pub public: u64,
+    pub reserved: u64,
+    pub founders: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistEligibility {
+    // Stored on the list and approved. Members of a merkle
+    // root list are only known once they send a proof
+    pub listed: bool,
+    pub tier: Option<String>,
+    pub allowance: u64,
+    pub minted: u64,
+    pub remaining: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintEligibility {
+    pub phase: Phase,
+    pub can_mint: bool,
+    // Why `can_mint` is false
+    pub reason: Option<String>,
+    // Native price per token in the current phase
+    pub price: Uint128,
+    pub private_whitelist: WhitelistEligibility,
+    pub public_whitelist: WhitelistEligibility,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistMemberInfo {
+    pub address: Addr,
+    pub whitelisted: bool,
+    pub allowance: u64,
+    pub tier: Option<String>,
+    pub minted: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistMembers {
+    pub members: Vec<WhitelistMemberInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistApplicationInfo {
+    pub applicant: Addr,
+    pub list: WhitelistKind,
+    pub deposit: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistApplications {
+    pub applications: Vec<WhitelistApplicationInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayeeInfo {
+    pub address: Addr,
+    pub share_bps: u16,
+    // Paid to the payee by the next Distribute
+    pub owed: Uint128,
+    pub claimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payees {
+    pub payees: Vec<PayeeInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PhaseRaised {
+    pub phase: String,
+    // Native denom or cw20 address
+    pub asset: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetAmount {
+    // Native denom or cw20 address
+    pub asset: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SaleStats {
+    // Kept by the contract, after platform fees and refunds
+    pub raised: Vec<PhaseRaised>,
+    // Paid out by Withdraw and Distribute
+    pub withdrawn: Vec<AssetAmount>,
+    pub unique_minters: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ownership {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub artist: Addr,
+    pub pending_artist: Option<Addr>,
+}
+
+// Pagination of whitelist members and applications
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn query_config(deps: Deps, env: Env) -> Result<QueriedState, ContractError> {
+    let mut config: State = STATE.load(deps.storage)?;
+    // Report the phase in effect, including scheduled transitions
+    config.phase = config.current_phase(env.block.time);
     Ok(config.into())
 }
 
@@ -33,6 +147,218 @@ pub fn query_whitelist_member(deps: Deps, address: Addr) -> Result<WhitelistMemb
     Ok(query_resp)
 }
 
+pub fn query_minted_count(deps: Deps) -> Result<MintedCount, ContractError> {
+    let phase_minted = |phase: Phase| -> StdResult<u64> {
+        Ok(PHASE_MINTED.may_load(deps.storage, phase.as_str())?.unwrap_or_default())
+    };
+    let reserved = RESERVED_MINTED.may_load(deps.storage)?.unwrap_or_default();
+    Ok(MintedCount {
+        total: MINTED_COUNT.may_load(deps.storage)?.unwrap_or_default(),
+        private_whitelist: phase_minted(Phase::PrivateWhitelist)?,
+        normal_whitelist: phase_minted(Phase::NormalWhitelist)?,
+        public: phase_minted(Phase::Public)?,
+        reserved: reserved.reserved,
+        founders: reserved.founders,
+    })
+}
+
+pub fn query_mint_eligibility(deps: Deps, env: Env, address: Addr) -> Result<MintEligibility, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let phase = state.current_phase(env.block.time);
+
+    let private_whitelist = whitelist_eligibility(
+        deps,
+        &PRIVATE_WHITELIST,
+        &PRIVATE_WHITELIST_COUNTER,
+        state.private_whitelist_allowance,
+        &address,
+    )?;
+    let public_whitelist = whitelist_eligibility(
+        deps,
+        &PUBLIC_WHITELIST,
+        &PUBLIC_WHITELIST_COUNTER,
+        state.public_whitelist_allowance,
+        &address,
+    )?;
+    let price = mint_price(deps.storage, &state, &phase, &address, &MintMsg::default())?;
+
+    // Same supply cap as minting, reserved tokens are held back
+    let minted_count = MINTED_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    let reserved = RESERVED_MINTED.may_load(deps.storage)?.unwrap_or_default();
+    let public_minted = minted_count.saturating_sub(reserved.reserved + reserved.founders);
+
+    let whitelist = if phase.is_private_whitelist() {
+        Some((&private_whitelist, &state.private_whitelist_merkle_root))
+    } else if phase.is_public_whitelist() {
+        Some((&public_whitelist, &state.public_whitelist_merkle_root))
+    } else {
+        None
+    };
+    let escrow_failed = escrow_status(deps.storage, &state, &env)? == Some(EscrowStatus::Failed);
+    let reason = if state.paused {
+        Some("Minting is paused")
+    } else if phase.is_disabled() {
+        Some("Minting has not started")
+    } else if phase.is_reveal() || escrow_failed {
+        Some("Minting period expired")
+    } else if public_minted >= state.public_supply() {
+        Some("All tokens distributed")
+    } else {
+        match whitelist {
+            Some((whitelist, Some(_))) if !whitelist.listed && whitelist.remaining > 0 => {
+                Some("Merkle proof required")
+            }
+            Some((whitelist, None)) if !whitelist.listed => Some("Not whitelisted"),
+            Some((whitelist, _)) if whitelist.remaining == 0 => Some("Whitelist allowance exceeded"),
+            _ => None,
+        }
+    };
+
+    Ok(MintEligibility {
+        phase,
+        can_mint: reason.is_none(),
+        reason: reason.map(String::from),
+        price,
+        private_whitelist,
+        public_whitelist,
+    })
+}
+
+fn whitelist_eligibility<'a>(
+    deps: Deps,
+    whitelist: &Map<'a, &'a Addr, WhitelistMember>,
+    counter: &Map<'a, &'a Addr, u64>,
+    default_allowance: u64,
+    address: &'a Addr,
+) -> StdResult<WhitelistEligibility> {
+    let whitelist_member = whitelist.may_load(deps.storage, address)?.unwrap_or_default();
+    let allowance = whitelist_member.allowance.unwrap_or(default_allowance);
+    let minted = counter.may_load(deps.storage, address)?.unwrap_or_default();
+    Ok(WhitelistEligibility {
+        listed: whitelist_member.whitelisted,
+        tier: whitelist_member.tier,
+        allowance,
+        minted,
+        remaining: allowance.saturating_sub(minted),
+    })
+}
+
+pub fn query_whitelist_members(
+    deps: Deps,
+    list: WhitelistKind,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> Result<WhitelistMembers, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let default_allowance = list.default_allowance(&state);
+    let counter = list.counter();
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let members = list
+        .members()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, member) = item?;
+            let minted = counter.may_load(deps.storage, &address)?.unwrap_or_default();
+            Ok(WhitelistMemberInfo {
+                whitelisted: member.whitelisted,
+                allowance: member.allowance.unwrap_or(default_allowance),
+                tier: member.tier,
+                minted,
+                address,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(WhitelistMembers { members })
+}
+
+pub fn query_whitelist_applications(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> Result<WhitelistApplications, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let applications = WHITELIST_APPLICATIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (applicant, application) = item?;
+            Ok(WhitelistApplicationInfo {
+                applicant,
+                list: application.list,
+                deposit: application.deposit,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(WhitelistApplications { applications })
+}
+
+pub fn query_payees(deps: Deps, env: Env, cw20: Option<Addr>) -> Result<Payees, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let balance = distributable_balance(deps, &env, &state, &cw20)?;
+    let asset = asset_key(&state, &cw20);
+
+    let payees = state
+        .payees
+        .iter()
+        .map(|payee| {
+            let claimed = PAYEE_CLAIMED
+                .may_load(deps.storage, (&payee.address, asset.as_str()))?
+                .unwrap_or_default();
+            Ok(PayeeInfo {
+                address: payee.address.clone(),
+                share_bps: payee.share_bps,
+                owed: balance.multiply_ratio(payee.share_bps, 10000_u128),
+                claimed,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Payees { payees })
+}
+
+pub fn query_sale_stats(deps: Deps) -> Result<SaleStats, ContractError> {
+    let raised = PHASE_RAISED
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let ((phase, asset), amount) = item?;
+            Ok(PhaseRaised { phase, asset, amount })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let withdrawn = WITHDRAWN
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (asset, amount) = item?;
+            Ok(AssetAmount { asset, amount })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SaleStats {
+        raised,
+        withdrawn,
+        unique_minters: UNIQUE_MINTERS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_ownership(deps: Deps) -> Result<Ownership, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    Ok(Ownership {
+        owner: state.owner,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+        artist: state.artist,
+        pending_artist: PENDING_ARTIST.may_load(deps.storage)?,
+    })
+}
+
+pub fn query_whitelist_stats(deps: Deps, list: WhitelistKind) -> Result<WhitelistStats, ContractError> {
+    Ok(list.stats().may_load(deps.storage)?.unwrap_or_default())
+}
+
 /// Accepts a list of token_ids as argument and returns
 /// nft info, for those nfts, separated by statuses.
 /// E.g. a list of `unrevealed` tokens, and a list of
@@ -1,10 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, StdResult, Storage, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, JsonSchema, Default)]
 pub enum Phase {
     #[default]
     Disabled,
@@ -21,11 +21,295 @@ impl Phase {
     pub fn is_public_whitelist(&self) -> bool { *self == Phase::NormalWhitelist }
     pub fn is_public_mint(&self) -> bool { *self == Phase::Public }
     pub fn is_reveal(&self) -> bool { *self == Phase::Reveal }
+    pub fn is_minting(&self) -> bool { self.is_enabled() && !self.is_reveal() }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Disabled => "disabled",
+            Phase::PrivateWhitelist => "private_whitelist",
+            Phase::NormalWhitelist => "normal_whitelist",
+            Phase::Public => "public",
+            Phase::Reveal => "reveal",
+        }
+    }
+}
+
+/// Optional start times of each phase. Once the block time reaches a
+/// scheduled start, that phase is in effect without the admin having
+/// to broadcast the matching `Enable*` transaction
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PhaseSchedule {
+    pub private_whitelist: Option<Timestamp>,
+    pub normal_whitelist: Option<Timestamp>,
+    pub public: Option<Timestamp>,
+    pub reveal: Option<Timestamp>,
+}
+
+impl PhaseSchedule {
+    /// Latest scheduled phase that has started at `now`, if any
+    pub fn phase_at(&self, now: Timestamp) -> Option<Phase> {
+        [
+            (Phase::Reveal, self.reveal),
+            (Phase::Public, self.public),
+            (Phase::NormalWhitelist, self.normal_whitelist),
+            (Phase::PrivateWhitelist, self.private_whitelist),
+        ]
+        .into_iter()
+        .find(|(_, start)| start.is_some_and(|start| start <= now))
+        .map(|(phase, _)| phase)
+    }
+
+    /// Scheduled phases must start in the order they are played
+    pub fn is_valid(&self) -> bool {
+        let starts: Vec<Timestamp> = [
+            self.private_whitelist,
+            self.normal_whitelist,
+            self.public,
+            self.reveal,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        starts.windows(2).all(|pair| pair[0] <= pair[1])
+    }
+}
+
+/// Price per token when minting with a cw20 token,
+/// in every phase
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw20Price {
+    pub address: Addr,
+    pub amount: Uint128,
+}
+
+/// Tokens minted so far from each reserved allocation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ReservedMinted {
+    pub reserved: u64,
+    pub founders: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
 pub struct WhitelistMember {
     pub whitelisted: bool,
+    // Overrides the whitelist allowance from `State`
+    pub allowance: Option<u64>,
+    // Name of a `State.whitelist_tiers` entry setting the price
+    pub tier: Option<String>,
+}
+
+impl WhitelistMember {
+    /// Whether a member who minted `minted` tokens has used up
+    /// their allowance
+    pub fn is_exhausted(&self, minted: u64, default_allowance: u64) -> bool {
+        minted > 0 && minted >= self.allowance.unwrap_or(default_allowance)
+    }
+}
+
+/// Running totals of a stored whitelist, kept up to date as
+/// members are approved, removed and mint
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct WhitelistStats {
+    pub member_count: u64,
+    // Includes tokens minted with merkle proofs
+    pub total_minted: u64,
+    // Members who have used their full allowance
+    pub exhausted_count: u64,
+}
+
+/// Selects one of the two stored whitelists
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitelistKind {
+    Private,
+    Public,
+}
+
+impl WhitelistKind {
+    pub fn members(&self) -> Map<'static, &'static Addr, WhitelistMember> {
+        match self {
+            WhitelistKind::Private => PRIVATE_WHITELIST,
+            WhitelistKind::Public => PUBLIC_WHITELIST,
+        }
+    }
+
+    pub fn counter(&self) -> Map<'static, &'static Addr, u64> {
+        match self {
+            WhitelistKind::Private => PRIVATE_WHITELIST_COUNTER,
+            WhitelistKind::Public => PUBLIC_WHITELIST_COUNTER,
+        }
+    }
+
+    pub fn stats(&self) -> Item<'static, WhitelistStats> {
+        match self {
+            WhitelistKind::Private => PRIVATE_WHITELIST_STATS,
+            WhitelistKind::Public => PUBLIC_WHITELIST_STATS,
+        }
+    }
+
+    /// Saves `member` to the list, updating its stats
+    pub fn save_member(
+        &self,
+        storage: &mut dyn Storage,
+        state: &State,
+        address: &Addr,
+        member: &WhitelistMember,
+    ) -> StdResult<()> {
+        let default_allowance = self.default_allowance(state);
+        let minted = self.counter().may_load(storage, address)?.unwrap_or_default();
+        let mut stats = self.stats().may_load(storage)?.unwrap_or_default();
+        match self.members().may_load(storage, address)? {
+            Some(existing) => {
+                if existing.is_exhausted(minted, default_allowance) {
+                    stats.exhausted_count -= 1;
+                }
+                update_tier_members(storage, &existing.tier, -1)?;
+            }
+            None => stats.member_count += 1,
+        }
+        if member.is_exhausted(minted, default_allowance) {
+            stats.exhausted_count += 1;
+        }
+        update_tier_members(storage, &member.tier, 1)?;
+        self.members().save(storage, address, member)?;
+        self.stats().save(storage, &stats)
+    }
+
+    /// Removes `address` from the list, if present, updating
+    /// its stats
+    pub fn remove_member(&self, storage: &mut dyn Storage, state: &State, address: &Addr) -> StdResult<()> {
+        let existing = match self.members().may_load(storage, address)? {
+            Some(existing) => existing,
+            None => return Ok(()),
+        };
+        let minted = self.counter().may_load(storage, address)?.unwrap_or_default();
+        let mut stats = self.stats().may_load(storage)?.unwrap_or_default();
+        stats.member_count -= 1;
+        if existing.is_exhausted(minted, self.default_allowance(state)) {
+            stats.exhausted_count -= 1;
+        }
+        update_tier_members(storage, &existing.tier, -1)?;
+        self.members().remove(storage, address);
+        self.stats().save(storage, &stats)
+    }
+
+    /// Allowance of members without one of their own
+    pub fn default_allowance(&self, state: &State) -> u64 {
+        match self {
+            WhitelistKind::Private => state.private_whitelist_allowance,
+            WhitelistKind::Public => state.public_whitelist_allowance,
+        }
+    }
+}
+
+// Adds `delta` to the member count of `tier`, if any
+fn update_tier_members(storage: &mut dyn Storage, tier: &Option<String>, delta: i64) -> StdResult<()> {
+    if let Some(tier) = tier {
+        let count = TIER_MEMBERS.may_load(storage, tier)?.unwrap_or_default();
+        TIER_MEMBERS.save(storage, tier, &count.saturating_add_signed(delta))?;
+    }
+    Ok(())
+}
+
+/// Share of the contract balance paid out by `Distribute`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payee {
+    pub address: Addr,
+    pub share_bps: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeAmount {
+    // Share of the payment, in basis points
+    Bps(u16),
+    // Per token minted, in the native denom
+    Flat(Uint128),
+}
+
+/// Launchpad fee sent to `address` from every mint payment,
+/// in the asset paid. Reserved mints are not charged
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlatformFee {
+    pub address: Addr,
+    pub amount: FeeAmount,
+}
+
+impl PlatformFee {
+    pub fn is_valid(&self) -> bool {
+        match self.amount {
+            FeeAmount::Bps(bps) => bps <= 10000,
+            FeeAmount::Flat(_) => true,
+        }
+    }
+
+    /// Fee taken from `payment` for `quantity` tokens, never
+    /// more than the payment itself
+    pub fn fee(&self, payment: Uint128, quantity: u64) -> Uint128 {
+        let fee = match self.amount {
+            FeeAmount::Bps(bps) => payment.multiply_ratio(bps, 10000_u128),
+            FeeAmount::Flat(amount) => amount.saturating_mul(Uint128::from(quantity)),
+        };
+        fee.min(payment)
+    }
+}
+
+/// Pending `WhitelistAdd` application, awaiting admin review
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistApplication {
+    pub list: WhitelistKind,
+    pub deposit: Uint128,
+}
+
+/// Named price for a group of whitelist members, in the native
+/// denom (e.g. OG holders minting cheaper than allowlisters)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistTier {
+    pub name: String,
+    pub price: Uint128,
+}
+
+/// Minimum raise for the sale. Payments stay in the contract
+/// until `min_tokens` have been sold; if `deadline` passes
+/// first, minters can claim refunds for their tokens
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Escrow {
+    pub min_tokens: u64,
+    pub deadline: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EscrowStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+impl Escrow {
+    /// Status once `sold` tokens have been sold, at `now`
+    pub fn status(&self, sold: u64, now: Timestamp) -> EscrowStatus {
+        if sold >= self.min_tokens {
+            EscrowStatus::Succeeded
+        } else if now >= self.deadline {
+            EscrowStatus::Failed
+        } else {
+            EscrowStatus::Pending
+        }
+    }
+}
+
+/// Paid for a single token while the sale is in escrow
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenPayment {
+    // Phase whose raised total includes `amount`
+    pub phase: Phase,
+    // Native `denom` when unset
+    pub cw20: Option<Addr>,
+    // Kept by the sale
+    pub amount: Uint128,
+    // Platform fee held in escrow along with `amount`
+    pub fee: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -35,16 +319,99 @@ pub struct State {
     pub artist: Addr,
     pub supply: u64,
     pub phase: Phase,
+    // Emergency stop, see `ExecuteMsg::Pause`. The phase is
+    // kept while paused
+    pub paused: bool,
+    pub schedule: PhaseSchedule,
+
+    // Held back from public supply, for MintReserved
+    pub total_reserved: u64,
+    pub total_reserved_founders: u64,
+
+    // Assign random token ids instead of sequential ones
+    pub shuffle_token_ids: bool,
 
     pub private_whitelist_allowance: u64,
     pub public_whitelist_allowance: u64,
+    pub private_whitelist_price: Uint128,
+    pub public_whitelist_price: Uint128,
+    pub whitelist_tiers: Vec<WhitelistTier>,
+    // Paid in `denom` with whitelist applications, refunded
+    // if rejected. Zero for free applications
+    pub whitelist_deposit: Uint128,
     pub price: Uint128,
+    // Native denom accepted as payment (e.g. "aarch" on
+    // mainnet, "aconst" on constantine)
+    pub denom: String,
+    pub cw20_price: Option<Cw20Price>,
     pub name_prefix: String,
+
+    // Split of the revenue by `Distribute`. When empty the
+    // artist withdraws instead
+    pub payees: Vec<Payee>,
+    pub platform_fee: Option<PlatformFee>,
+
+    // Hex encoded sha256 roots proving whitelist membership
+    // without storing every address
+    pub private_whitelist_merkle_root: Option<String>,
+    pub public_whitelist_merkle_root: Option<String>,
+
+    // Secp256k1 public key of the backend issuing mint vouchers
+    pub voucher_signer: Option<Binary>,
+
+    // Refundable sale, see `Escrow`
+    pub escrow: Option<Escrow>,
+}
+
+impl State {
+    /// Phase in effect at `now`. A phase set manually by the admin
+    /// holds until a later phase reaches its scheduled start
+    pub fn current_phase(&self, now: Timestamp) -> Phase {
+        match self.schedule.phase_at(now) {
+            Some(scheduled) if scheduled > self.phase => scheduled,
+            _ => self.phase.clone(),
+        }
+    }
+
+    /// Tokens that can be minted outside of the reserved
+    /// and founders allocations
+    pub fn public_supply(&self) -> u64 {
+        self.supply
+            .saturating_sub(self.total_reserved)
+            .saturating_sub(self.total_reserved_founders)
+    }
+
+    /// Minting price charged during `phase`. `price` applies
+    /// to the public mint
+    pub fn phase_price(&self, phase: &Phase) -> Uint128 {
+        match phase {
+            Phase::PrivateWhitelist => self.private_whitelist_price,
+            Phase::NormalWhitelist => self.public_whitelist_price,
+            _ => self.price,
+        }
+    }
+
+    /// Payees are either unset, or all have a share and the
+    /// shares add up to the whole balance
+    pub fn payees_valid(&self) -> bool {
+        let total: u32 = self.payees.iter().map(|payee| payee.share_bps as u32).sum();
+        self.payees.is_empty()
+            || (total == 10000 && self.payees.iter().all(|payee| payee.share_bps > 0))
+    }
+
+    /// Price of the whitelist tier named `tier`, if it exists
+    pub fn tier_price(&self, tier: &str) -> Option<Uint128> {
+        self.whitelist_tiers
+            .iter()
+            .find(|whitelist_tier| whitelist_tier.name == tier)
+            .map(|whitelist_tier| whitelist_tier.price)
+    }
 }
 
 impl Into<QueriedState> for State {
     fn into(self) -> QueriedState {
         QueriedState {
+            price: self.phase_price(&self.phase),
             owner: self.owner,
             cw721: self.cw721,
             artist: self.artist,
@@ -53,16 +420,31 @@ impl Into<QueriedState> for State {
             whitelist_allowance: self.public_whitelist_allowance,
             private_whitelist_allowance: self.private_whitelist_allowance,
 
-            total_reserved: 0,
-            total_reserved_founders: 0,
+            total_reserved: self.total_reserved,
+            total_reserved_founders: self.total_reserved_founders,
+            shuffle_token_ids: self.shuffle_token_ids,
 
-            price: self.price,
+            private_whitelist_price: self.private_whitelist_price,
+            public_whitelist_price: self.public_whitelist_price,
+            whitelist_tiers: self.whitelist_tiers,
+            whitelist_deposit: self.whitelist_deposit,
+            public_price: self.price,
+            denom: self.denom,
+            cw20_price: self.cw20_price,
             name_prefix: self.name_prefix,
+            payees: self.payees,
+            platform_fee: self.platform_fee,
+            schedule: self.schedule,
+            private_whitelist_merkle_root: self.private_whitelist_merkle_root,
+            public_whitelist_merkle_root: self.public_whitelist_merkle_root,
+            voucher_signer: self.voucher_signer,
+            escrow: self.escrow,
 
             public_whitelist: self.phase.is_public_whitelist(),
             public_mint: self.phase.is_public_mint(),
             reveal: self.phase.is_reveal(),
             initialized: self.phase.is_enabled(),
+            paused: self.paused,
         }
     }
 }
@@ -78,21 +460,69 @@ pub struct QueriedState {
     pub whitelist_allowance: u64,
     pub private_whitelist_allowance: u64,
 
-    // TODO: review later
     pub total_reserved: u64,
     pub total_reserved_founders: u64,
-    
+    pub shuffle_token_ids: bool,
+
+    // Price in effect for the current phase
     pub price: Uint128,
+    pub private_whitelist_price: Uint128,
+    pub public_whitelist_price: Uint128,
+    pub whitelist_tiers: Vec<WhitelistTier>,
+    pub whitelist_deposit: Uint128,
+    pub public_price: Uint128,
+    pub denom: String,
+    pub cw20_price: Option<Cw20Price>,
     pub name_prefix: String,
+    pub payees: Vec<Payee>,
+    pub platform_fee: Option<PlatformFee>,
+    pub schedule: PhaseSchedule,
+    pub private_whitelist_merkle_root: Option<String>,
+    pub public_whitelist_merkle_root: Option<String>,
+    pub voucher_signer: Option<Binary>,
+    pub escrow: Option<Escrow>,
 
     pub public_whitelist: bool,
     pub public_mint: bool,
     pub reveal: bool,
     pub initialized: bool,
+    pub paused: bool,
 }
 
 pub const STATE: Item<State> = Item::new("state");
+// Proposed by the current holder of the role, until accepted
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+pub const PENDING_ARTIST: Item<Addr> = Item::new("pending_artist");
+pub const RESERVED_MINTED: Item<ReservedMinted> = Item::new("reserved_minted");
+// Token ids are derived from this count, so burning
+// tokens never leads to an id being minted twice
+pub const MINTED_COUNT: Item<u64> = Item::new("minted_count");
+pub const PHASE_MINTED: Map<&str, u64> = Map::new("phase_minted");
+// Mint revenue kept by the contract, by phase and by native
+// denom or cw20 address
+pub const PHASE_RAISED: Map<(&str, &str), Uint128> = Map::new("phase_raised");
+// Paid out by Withdraw and Distribute, by native denom or cw20 address
+pub const WITHDRAWN: Map<&str, Uint128> = Map::new("withdrawn");
+pub const MINTERS: Map<&Addr, bool> = Map::new("minters");
+pub const UNIQUE_MINTERS: Item<u64> = Item::new("unique_minters");
+pub const TOKEN_ID_POOL: Map<u64, u64> = Map::new("token_id_pool");
+// Payment held for each token id while the sale is in escrow
+pub const TOKEN_PAYMENTS: Map<&str, TokenPayment> = Map::new("token_payments");
+// Platform fees held until the escrow is released, by native
+// denom or cw20 address
+pub const ESCROWED_FEES: Map<&str, Uint128> = Map::new("escrowed_fees");
+pub const USED_VOUCHER_NONCES: Map<u64, bool> = Map::new("used_voucher_nonces");
 pub const PUBLIC_WHITELIST: Map<&Addr, WhitelistMember> = Map::new("public_whitelist");
 pub const PUBLIC_WHITELIST_COUNTER: Map<&Addr, u64> = Map::new("public_whitelist_counter");
 pub const PRIVATE_WHITELIST: Map<&Addr, WhitelistMember> = Map::new("private_whitelist");
-pub const PRIVATE_WHITELIST_COUNTER: Map<&Addr, u64> = Map::new("private_whitelist_counter");
\ No newline at end of file
+pub const PRIVATE_WHITELIST_COUNTER: Map<&Addr, u64> = Map::new("private_whitelist_counter");
+pub const PUBLIC_WHITELIST_STATS: Item<WhitelistStats> = Item::new("public_whitelist_stats");
+pub const PRIVATE_WHITELIST_STATS: Item<WhitelistStats> = Item::new("private_whitelist_stats");
+// Members of either whitelist assigned to each tier, so tiers
+// in use can't be removed
+pub const TIER_MEMBERS: Map<&str, u64> = Map::new("tier_members");
+pub const WHITELIST_APPLICATIONS: Map<&Addr, WhitelistApplication> = Map::new("whitelist_applications");
+// Deposits of pending applications, held back from `Distribute`
+pub const PENDING_DEPOSITS: Item<Uint128> = Item::new("pending_deposits");
+// Paid to each payee, by native denom or cw20 address
+pub const PAYEE_CLAIMED: Map<(&Addr, &str), Uint128> = Map::new("payee_claimed");
\ No newline at end of file
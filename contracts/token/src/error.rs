@@ -11,6 +11,18 @@ pub enum ContractError {
     #[error(transparent)]
     Cw721(#[from] Cw721Error),
 
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Cannot migrate from version {stored} to {current}")]
+    CannotMigrate { stored: String, current: String },
+
     #[error("Address cannot do this")]
     Unauthorized {}
 }
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
+}
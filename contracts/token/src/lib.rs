@@ -1,8 +1,10 @@
 pub mod msgs;
 pub mod error;
+mod tests;
 
 use cosmwasm_std::{Empty, Reply, SubMsgResult};
 use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
 
 pub use {{cw721_base_lib}} as cw721_base;
 pub use {{cw721_lib}} as cw721;
@@ -72,15 +74,28 @@ pub mod entry {
     #[cfg_attr(not(feature = "library"), entry_point)]
     pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
         let original_version = get_contract_version(deps.storage)?;
-        let name = CONTRACT_NAME.to_string();
-        let version = CONTRACT_VERSION.to_string();
-        if original_version.contract != name {
-            return Err(ContractError::Unauthorized {});
-        }
-        if original_version.version >= version {
+        if original_version.contract != CONTRACT_NAME {
             return Err(ContractError::Unauthorized {});
         }
+
+        ensure_upgrade(&original_version.version, CONTRACT_VERSION)?;
+
+        // No storage changes between released versions yet
         set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
         Ok(Response::default())
     }
-}
\ No newline at end of file
+}
+
+/// Only upgrades are allowed, compared as semver so that
+/// "1.10.0" is newer than "1.9.0"
+pub fn ensure_upgrade(stored: &str, current: &str) -> Result<(), ContractError> {
+    let stored_version: Version = stored.parse()?;
+    let version: Version = current.parse()?;
+    if stored_version >= version {
+        return Err(ContractError::CannotMigrate {
+            stored: stored.to_string(),
+            current: current.to_string(),
+        });
+    }
+    Ok(())
+}
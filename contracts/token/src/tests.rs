@@ -0,0 +1,55 @@
+#![cfg(test)]
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cw2::{get_contract_version, set_contract_version};
+use rstest::rstest;
+
+use crate::entry::migrate;
+use crate::{ensure_upgrade, ContractError, MigrateMsg, CONTRACT_NAME, CONTRACT_VERSION};
+
+// A token deployed with an older version is upgraded to
+// the current one
+#[test]
+fn test_migrate() {
+    let mut deps = mock_dependencies();
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "1.0.1").unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.version, CONTRACT_VERSION);
+}
+
+// Versions are compared as semver, so only upgrades go through
+#[rstest]
+#[case(CONTRACT_VERSION)]
+#[case("1.10.0")]
+#[case("10.0.0")]
+fn test_migrate_rejects_downgrades(#[case] stored: &str) {
+    let mut deps = mock_dependencies();
+    set_contract_version(deps.as_mut().storage, CONTRACT_NAME, stored).unwrap();
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CannotMigrate {
+            stored: stored.to_string(),
+            current: CONTRACT_VERSION.to_string(),
+        }
+    );
+}
+
+#[rstest]
+#[case("1.9.0", "1.10.0", true)]
+#[case("1.10.0", "1.9.0", false)]
+#[case("1.9.0", "1.9.0", false)]
+fn test_ensure_upgrade(#[case] stored: &str, #[case] current: &str, #[case] upgrade: bool) {
+    assert_eq!(ensure_upgrade(stored, current).is_ok(), upgrade);
+}
+
+#[test]
+fn test_migrate_other_contract() {
+    let mut deps = mock_dependencies();
+    set_contract_version(deps.as_mut().storage, "crates.io:cw721-base", "0.1.0").unwrap();
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}